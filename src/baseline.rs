@@ -0,0 +1,392 @@
+use crate::fingerprint;
+use crate::models::DebtMarker;
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Default baseline file name, at the repository root
+pub const BASELINE_FILE: &str = ".fossil-baseline.json";
+
+/// Bump when the baseline format changes
+const BASELINE_VERSION: u32 = 1;
+
+/// A marker accepted into the baseline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaselineEntry {
+    /// Marker fingerprint
+    pub fingerprint: String,
+
+    /// Type of marker, for reviewing the baseline
+    pub marker_type: String,
+
+    /// File the marker was in, relative to the repository root
+    pub file_path: PathBuf,
+
+    /// Line the marker was on when the entry was written
+    pub line_number: usize,
+
+    /// Marker description, for reviewing the baseline
+    pub description: String,
+}
+
+/// Markers that existed when the baseline was written, identified by
+/// fingerprint, so scans can report only debt introduced since
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Baseline {
+    /// Format version of the baseline file
+    pub version: u32,
+
+    /// When the baseline was last written
+    pub updated_at: DateTime<Utc>,
+
+    /// HEAD commit when the baseline was last written, used to follow renames
+    pub commit: Option<String>,
+
+    /// Accepted markers, sorted by file and line
+    pub entries: Vec<BaselineEntry>,
+}
+
+/// How the markers of a scan line up with a baseline
+#[derive(Debug, Clone, PartialEq)]
+pub struct BaselineMatch {
+    /// Indices of markers that have a baseline entry
+    pub known: Vec<usize>,
+
+    /// Indices of markers missing from the baseline
+    pub new: Vec<usize>,
+
+    /// Indices of baseline entries without a marker
+    pub stale: Vec<usize>,
+}
+
+/// Where scanned markers come from, to relate their paths to the repository
+#[derive(Debug, Clone, Copy)]
+pub struct ScanLocation<'a> {
+    /// Path that was scanned
+    pub scan_path: &'a Path,
+
+    /// Location of `scan_path` relative to the repository root
+    pub prefix: &'a Path,
+
+    /// Files renamed since the baseline was written (old → new path)
+    pub renames: &'a HashMap<PathBuf, PathBuf>,
+}
+
+fn entry_for(marker: &DebtMarker, location: ScanLocation) -> BaselineEntry {
+    BaselineEntry {
+        fingerprint: marker.fingerprint.clone(),
+        marker_type: marker.marker_type.clone(),
+        file_path: fingerprint::repo_path(marker, location.scan_path, location.prefix),
+        line_number: marker.line_number,
+        description: marker.description.clone(),
+    }
+}
+
+impl Baseline {
+    /// Create a baseline accepting every marker
+    pub fn new(markers: &[DebtMarker], location: ScanLocation, commit: Option<String>) -> Self {
+        let mut baseline = Self {
+            version: BASELINE_VERSION,
+            updated_at: Utc::now(),
+            commit,
+            entries: markers.iter().map(|m| entry_for(m, location)).collect(),
+        };
+        baseline.sort();
+        baseline
+    }
+
+    /// Load a baseline file
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read baseline: {}", path.display()))?;
+        let baseline: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse baseline: {}", path.display()))?;
+
+        if baseline.version > BASELINE_VERSION {
+            bail!(
+                "Baseline {} was written by a newer version of fossil (format {})",
+                path.display(),
+                baseline.version
+            );
+        }
+
+        Ok(baseline)
+    }
+
+    /// Write the baseline file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents =
+            serde_json::to_string_pretty(self).context("Failed to serialize baseline")?;
+        fs::write(path, contents + "\n")
+            .with_context(|| format!("Failed to write baseline: {}", path.display()))
+    }
+
+    fn sort(&mut self) {
+        self.entries.sort_by(|a, b| {
+            a.file_path
+                .cmp(&b.file_path)
+                .then(a.line_number.cmp(&b.line_number))
+                .then(a.fingerprint.cmp(&b.fingerprint))
+        });
+    }
+
+    /// Pair markers with baseline entries by fingerprint
+    ///
+    /// Each entry matches at most one marker, so a duplicated TODO is new even
+    /// if an identical one is in the baseline. Markers in files renamed since
+    /// the baseline was written also match entries under the old path.
+    pub fn compare(&self, markers: &[DebtMarker], location: ScanLocation) -> BaselineMatch {
+        let mut matcher = self.matcher(location);
+        let mut known = Vec::new();
+        let mut new = Vec::new();
+        for (idx, marker) in markers.iter().enumerate() {
+            if matcher.matches(marker) {
+                known.push(idx);
+            } else {
+                new.push(idx);
+            }
+        }
+
+        BaselineMatch {
+            known,
+            new,
+            stale: matcher.unmatched(),
+        }
+    }
+
+    /// Start matching markers against the baseline a few at a time, e.g. as
+    /// files are scanned (see [`Baseline::compare`])
+    pub fn matcher<'a>(&'a self, location: ScanLocation<'a>) -> BaselineMatcher<'a> {
+        let mut remaining: HashMap<&str, VecDeque<usize>> = HashMap::new();
+        for (idx, entry) in self.entries.iter().enumerate() {
+            remaining
+                .entry(entry.fingerprint.as_str())
+                .or_default()
+                .push_back(idx);
+        }
+
+        let old_paths = location
+            .renames
+            .iter()
+            .map(|(old, new)| (new.as_path(), old.as_path()))
+            .collect();
+
+        BaselineMatcher {
+            remaining,
+            old_paths,
+            location,
+        }
+    }
+
+    /// Markers that are not in the baseline
+    pub fn new_markers(&self, markers: Vec<DebtMarker>, location: ScanLocation) -> Vec<DebtMarker> {
+        self.matcher(location).new_markers(markers)
+    }
+
+    /// Accept markers missing from the baseline, returning how many were added
+    ///
+    /// Entries for existing markers are refreshed (following renames); entries
+    /// whose markers are gone are kept until pruned.
+    pub fn update(
+        &mut self,
+        markers: &[DebtMarker],
+        location: ScanLocation,
+        commit: Option<String>,
+    ) -> usize {
+        let matched = self.compare(markers, location);
+
+        let mut entries: Vec<BaselineEntry> = matched
+            .stale
+            .iter()
+            .map(|&idx| self.entries[idx].clone())
+            .collect();
+        entries.extend(markers.iter().map(|m| entry_for(m, location)));
+
+        self.entries = entries;
+        self.commit = commit;
+        self.updated_at = Utc::now();
+        self.sort();
+
+        matched.new.len()
+    }
+
+    /// Drop entries whose markers are gone, returning how many were removed
+    ///
+    /// Remaining entries are refreshed (following renames); new markers are
+    /// not added.
+    pub fn prune(
+        &mut self,
+        markers: &[DebtMarker],
+        location: ScanLocation,
+        commit: Option<String>,
+    ) -> usize {
+        let matched = self.compare(markers, location);
+
+        self.entries = matched
+            .known
+            .iter()
+            .map(|&idx| entry_for(&markers[idx], location))
+            .collect();
+        self.commit = commit;
+        self.updated_at = Utc::now();
+        self.sort();
+
+        matched.stale.len()
+    }
+}
+
+/// Matches markers against a baseline incrementally
+///
+/// Entries claimed by earlier markers stay claimed, so matching in batches
+/// gives the same result as matching all markers at once.
+pub struct BaselineMatcher<'a> {
+    /// Unclaimed entry indices by fingerprint
+    remaining: HashMap<&'a str, VecDeque<usize>>,
+
+    /// Old path of each renamed file, by new path
+    old_paths: HashMap<&'a Path, &'a Path>,
+
+    location: ScanLocation<'a>,
+}
+
+impl BaselineMatcher<'_> {
+    /// Whether a marker matches an unclaimed baseline entry, claiming it
+    pub fn matches(&mut self, marker: &DebtMarker) -> bool {
+        let location = self.location;
+        let mut candidates = vec![marker.fingerprint.clone()];
+        let path = fingerprint::repo_path(marker, location.scan_path, location.prefix);
+        if let Some(old_path) = self.old_paths.get(path.as_path()) {
+            candidates.push(fingerprint::fingerprint(marker, old_path));
+        }
+
+        candidates.iter().any(|fp| {
+            self.remaining
+                .get_mut(fp.as_str())
+                .and_then(VecDeque::pop_front)
+                .is_some()
+        })
+    }
+
+    /// Markers that don't match the baseline
+    pub fn new_markers(&mut self, markers: Vec<DebtMarker>) -> Vec<DebtMarker> {
+        markers
+            .into_iter()
+            .filter(|marker| !self.matches(marker))
+            .collect()
+    }
+
+    /// Indices of entries no marker has matched, in order
+    fn unmatched(self) -> Vec<usize> {
+        let mut stale: Vec<usize> = self.remaining.into_values().flatten().collect();
+        stale.sort_unstable();
+        stale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn markers(specs: &[(&str, &str)]) -> Vec<DebtMarker> {
+        let mut markers: Vec<DebtMarker> = specs
+            .iter()
+            .enumerate()
+            .map(|(idx, (file, description))| DebtMarker {
+                line_content: format!("// TODO: {}", description),
+                description: description.to_string(),
+                ..DebtMarker::test(file, idx + 1, "TODO")
+            })
+            .collect();
+        fingerprint::assign_fingerprints(&mut markers, Path::new("."), Path::new(""));
+        markers
+    }
+
+    fn location(renames: &HashMap<PathBuf, PathBuf>) -> ScanLocation<'_> {
+        ScanLocation {
+            scan_path: Path::new("."),
+            prefix: Path::new(""),
+            renames,
+        }
+    }
+
+    #[test]
+    fn test_new_markers() {
+        let no_renames = HashMap::new();
+        let old = markers(&[("./a.rs", "one"), ("./b.rs", "two")]);
+        let baseline = Baseline::new(&old, location(&no_renames), None);
+        assert_eq!(baseline.entries[0].file_path, PathBuf::from("a.rs"));
+
+        // A new marker and a duplicate of an accepted one are reported
+        let current = markers(&[("./a.rs", "one"), ("./a.rs", "one"), ("./c.rs", "three")]);
+        let new = baseline.new_markers(current, location(&no_renames));
+        assert_eq!(new.len(), 2);
+        assert_eq!(new[1].description, "three");
+
+        let matched = baseline.compare(&old[..1], location(&no_renames));
+        assert_eq!(matched.known, vec![0]);
+        assert_eq!(matched.stale, vec![1]);
+    }
+
+    #[test]
+    fn test_renamed_files_match() {
+        let no_renames = HashMap::new();
+        let baseline = Baseline::new(&markers(&[("./a.rs", "one")]), location(&no_renames), None);
+
+        let current = markers(&[("./renamed.rs", "one")]);
+        assert_eq!(
+            baseline
+                .new_markers(current.clone(), location(&no_renames))
+                .len(),
+            1
+        );
+
+        let renames = HashMap::from([(PathBuf::from("a.rs"), PathBuf::from("renamed.rs"))]);
+        assert!(baseline.new_markers(current, location(&renames)).is_empty());
+    }
+
+    #[test]
+    fn test_update_and_prune() {
+        let no_renames = HashMap::new();
+        let mut baseline = Baseline::new(
+            &markers(&[("./a.rs", "one"), ("./b.rs", "two")]),
+            location(&no_renames),
+            None,
+        );
+
+        let current = markers(&[("./a.rs", "one"), ("./c.rs", "three")]);
+        let added = baseline.update(&current, location(&no_renames), Some("abc".to_string()));
+        assert_eq!(added, 1);
+        assert_eq!(baseline.entries.len(), 3); // b.rs kept until pruned
+        assert_eq!(baseline.commit.as_deref(), Some("abc"));
+
+        let removed = baseline.prune(&current, location(&no_renames), None);
+        assert_eq!(removed, 1);
+        let files: Vec<_> = baseline
+            .entries
+            .iter()
+            .map(|e| e.file_path.clone())
+            .collect();
+        assert_eq!(files, vec![PathBuf::from("a.rs"), PathBuf::from("c.rs")]);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(BASELINE_FILE);
+        let no_renames = HashMap::new();
+
+        let baseline = Baseline::new(&markers(&[("./a.rs", "one")]), location(&no_renames), None);
+        baseline.save(&path).unwrap();
+        let loaded = Baseline::load(&path).unwrap();
+        assert_eq!(loaded.entries, baseline.entries);
+
+        let mut newer = baseline.clone();
+        newer.version = BASELINE_VERSION + 1;
+        newer.save(&path).unwrap();
+        assert!(Baseline::load(&path).is_err());
+    }
+}
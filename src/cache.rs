@@ -0,0 +1,344 @@
+use crate::models::GitBlameInfo;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Directory (relative to the repository root) holding fossil's caches
+pub const CACHE_DIR: &str = ".fossil/cache";
+
+/// Bump when the entry format changes; older entries are treated as misses
+const CACHE_VERSION: u32 = 2;
+
+/// Distinguishes temporary files written concurrently by blame workers
+static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A run of consecutive lines attributed to the same commit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlameHunk {
+    /// First line of the hunk (1-indexed)
+    pub start_line: usize,
+
+    /// Number of lines in the hunk
+    pub lines: usize,
+
+    /// Blame information shared by every line in the hunk
+    pub info: GitBlameInfo,
+}
+
+/// Find the blame information for a line in a list of hunks
+pub fn lookup_line(hunks: &[BlameHunk], line_number: usize) -> Option<&GitBlameInfo> {
+    hunks
+        .iter()
+        .find(|hunk| line_number >= hunk.start_line && line_number < hunk.start_line + hunk.lines)
+        .map(|hunk| &hunk.info)
+}
+
+/// Identifies a cached blame: the HEAD commit, the file's path and blob at
+/// HEAD, and the blame settings that affect the result
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlameCacheKey {
+    /// HEAD commit the blame is computed at
+    pub head: String,
+
+    /// Path of the file relative to the repository root
+    pub path: PathBuf,
+
+    /// Blob id of the file in the HEAD tree
+    pub blob: String,
+
+    /// Short tag describing the blame options used
+    pub variant: String,
+}
+
+impl BlameCacheKey {
+    /// Entry file name, within the directory for `head`
+    fn file_name(&self) -> String {
+        let digest = Sha256::digest(self.path.to_string_lossy().as_bytes());
+        let location: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+        format!("{}-{}-{}.json", self.blob, location, self.variant)
+    }
+}
+
+/// On-disk cache entry
+#[derive(Debug, Serialize, Deserialize)]
+struct BlameCacheEntry {
+    version: u32,
+
+    /// HEAD commit the blame was computed at
+    head: String,
+
+    /// Path of the file when the blame was computed
+    path: PathBuf,
+
+    hunks: Vec<BlameHunk>,
+}
+
+/// Persistent cache of per-file blame results under `.fossil/cache/blame`
+///
+/// Entries are keyed by HEAD commit, path and blob, so repeated scans of the
+/// same commit don't re-blame anything. The same content at another path, or
+/// at another commit, has its own history and is blamed separately. Each HEAD
+/// gets its own directory, so [`BlameCache::prune`] can drop the entries of
+/// earlier commits.
+#[derive(Debug, Clone)]
+pub struct BlameCache {
+    dir: PathBuf,
+}
+
+impl BlameCache {
+    /// Open (and create if needed) the blame cache for a repository
+    pub fn open(repo_root: &Path) -> Result<Self> {
+        let dir = repo_root.join(CACHE_DIR).join("blame");
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create cache directory: {}", dir.display()))?;
+        Ok(Self { dir })
+    }
+
+    /// Remove the entries of every HEAD but `head`, which will never be read
+    /// again, returning the number of files removed
+    pub fn prune(&self, head: &str) -> Result<usize> {
+        let entries = fs::read_dir(&self.dir)
+            .with_context(|| format!("Failed to read cache directory: {}", self.dir.display()))?;
+
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                if entry.file_name() == head {
+                    continue;
+                }
+                removed += count_files(&path);
+                fs::remove_dir_all(&path)
+            } else {
+                removed += 1;
+                fs::remove_file(&path)
+            }
+            .with_context(|| format!("Failed to remove cache entry: {}", path.display()))?;
+        }
+
+        Ok(removed)
+    }
+
+    fn entry_path(&self, key: &BlameCacheKey) -> PathBuf {
+        self.dir.join(&key.head).join(key.file_name())
+    }
+
+    /// Look up cached hunks, refreshing their age to today
+    ///
+    /// Corrupt or outdated entries are removed and reported as a miss.
+    pub fn get(&self, key: &BlameCacheKey) -> Option<Vec<BlameHunk>> {
+        let path = self.entry_path(key);
+        let contents = fs::read(&path).ok()?;
+
+        let entry: BlameCacheEntry = match serde_json::from_slice(&contents) {
+            Ok(entry) => entry,
+            Err(_) => {
+                let _ = fs::remove_file(&path);
+                return None;
+            }
+        };
+
+        if entry.version != CACHE_VERSION {
+            let _ = fs::remove_file(&path);
+            return None;
+        }
+
+        // The file name only holds a digest of these
+        if entry.head != key.head || entry.path != key.path {
+            return None;
+        }
+
+        let now = Utc::now();
+        let mut hunks = entry.hunks;
+        for hunk in &mut hunks {
+            hunk.info.age_days = now.signed_duration_since(hunk.info.commit_time).num_days();
+        }
+
+        Some(hunks)
+    }
+
+    /// Store hunks for a file
+    ///
+    /// The entry is written to a temporary file first and renamed into place,
+    /// so concurrent readers never see a partial entry.
+    pub fn put(&self, key: &BlameCacheKey, hunks: &[BlameHunk]) -> Result<()> {
+        let entry = BlameCacheEntry {
+            version: CACHE_VERSION,
+            head: key.head.clone(),
+            path: key.path.clone(),
+            hunks: hunks.to_vec(),
+        };
+        let contents = serde_json::to_vec(&entry).context("Failed to serialize cache entry")?;
+
+        let target = self.entry_path(key);
+        let dir = self.dir.join(&key.head);
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create cache directory: {}", dir.display()))?;
+        let tmp = dir.join(format!(
+            ".{}.{}.{}.tmp",
+            key.file_name(),
+            std::process::id(),
+            TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        fs::write(&tmp, contents)
+            .with_context(|| format!("Failed to write cache entry: {}", tmp.display()))?;
+        fs::rename(&tmp, &target).with_context(|| {
+            let _ = fs::remove_file(&tmp);
+            format!("Failed to write cache entry: {}", target.display())
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Remove all cached data for a repository, returning the number of files removed
+pub fn clear_cache(repo_root: &Path) -> Result<usize> {
+    let dir = repo_root.join(CACHE_DIR);
+    if !dir.exists() {
+        return Ok(0);
+    }
+
+    let removed = count_files(&dir);
+    fs::remove_dir_all(&dir)
+        .with_context(|| format!("Failed to remove cache directory: {}", dir.display()))?;
+
+    Ok(removed)
+}
+
+fn count_files(dir: &Path) -> usize {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| {
+                    let path = entry.path();
+                    if path.is_dir() { count_files(&path) } else { 1 }
+                })
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn test_hunks() -> Vec<BlameHunk> {
+        vec![BlameHunk {
+            start_line: 3,
+            lines: 2,
+            info: GitBlameInfo {
+                commit_time: Utc::now() - chrono::Duration::days(10),
+                ..GitBlameInfo::test("Alice", 0)
+            },
+        }]
+    }
+
+    fn test_key() -> BlameCacheKey {
+        BlameCacheKey {
+            head: "head".to_string(),
+            path: PathBuf::from("src/lib.rs"),
+            blob: "0123abcd".to_string(),
+            variant: "copies".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_lookup_line() {
+        let hunks = test_hunks();
+        assert!(lookup_line(&hunks, 2).is_none());
+        assert_eq!(lookup_line(&hunks, 3).unwrap().author, "Alice");
+        assert_eq!(lookup_line(&hunks, 4).unwrap().author, "Alice");
+        assert!(lookup_line(&hunks, 5).is_none());
+    }
+
+    #[test]
+    fn test_put_get_and_clear() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = BlameCache::open(temp_dir.path()).unwrap();
+        let key = test_key();
+
+        assert!(cache.get(&key).is_none());
+        cache.put(&key, &test_hunks()).unwrap();
+
+        let hunks = cache.get(&key).unwrap();
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].info.age_days, 10); // Age is refreshed on load
+
+        let other_variant = BlameCacheKey {
+            variant: "nocopies".to_string(),
+            ..key.clone()
+        };
+        assert!(cache.get(&other_variant).is_none());
+
+        // The same blob at another path or commit is a miss
+        let other_path = BlameCacheKey {
+            path: PathBuf::from("src/copy.rs"),
+            ..key.clone()
+        };
+        assert!(cache.get(&other_path).is_none());
+        let other_head = BlameCacheKey {
+            head: "rebased".to_string(),
+            ..key.clone()
+        };
+        assert!(cache.get(&other_head).is_none());
+
+        assert_eq!(clear_cache(temp_dir.path()).unwrap(), 1);
+        assert!(!temp_dir.path().join(CACHE_DIR).exists());
+        assert_eq!(clear_cache(temp_dir.path()).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_prune_other_heads() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = BlameCache::open(temp_dir.path()).unwrap();
+        let old = BlameCacheKey {
+            head: "old".to_string(),
+            ..test_key()
+        };
+        let current = test_key();
+        cache.put(&old, &test_hunks()).unwrap();
+        cache
+            .put(
+                &BlameCacheKey {
+                    path: PathBuf::from("src/main.rs"),
+                    ..old.clone()
+                },
+                &test_hunks(),
+            )
+            .unwrap();
+        cache.put(&current, &test_hunks()).unwrap();
+        // Flat entries from before entries were grouped by HEAD
+        fs::write(cache.dir.join("0123abcd-copies.json"), b"{}").unwrap();
+
+        assert_eq!(cache.prune(&current.head).unwrap(), 3);
+        assert!(cache.get(&old).is_none());
+        assert!(cache.get(&current).is_some());
+        assert_eq!(fs::read_dir(&cache.dir).unwrap().count(), 1);
+        assert_eq!(cache.prune(&current.head).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_corrupt_entry_is_removed() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = BlameCache::open(temp_dir.path()).unwrap();
+        let key = test_key();
+
+        let entry_path = cache.entry_path(&key);
+        fs::create_dir_all(entry_path.parent().unwrap()).unwrap();
+        fs::write(&entry_path, b"{ not json").unwrap();
+
+        assert!(cache.get(&key).is_none());
+        assert!(!entry_path.exists());
+
+        // The entry can be rewritten afterwards
+        cache.put(&key, &test_hunks()).unwrap();
+        assert!(cache.get(&key).is_some());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::models::{GroupKey, Severity, SortKey, SortOrder};
 use clap::{Parser, ValueEnum};
 use std::path::PathBuf;
 
@@ -13,6 +14,213 @@ pub struct Cli {
 pub enum Commands {
     /// Scan a directory for technical debt markers
     Scan(ScanArgs),
+
+    /// Manage fossil's on-disk caches
+    Cache(CacheArgs),
+
+    /// Track technical debt over time
+    History(HistoryArgs),
+
+    /// Show debt added, removed and moved between two git revisions
+    Diff(DiffArgs),
+
+    /// Record existing debt so scans report only new markers
+    Baseline(BaselineArgs),
+
+    /// Rank files where debt and recent churn overlap
+    Hotspots(HotspotsArgs),
+}
+
+#[derive(Parser, Debug)]
+pub struct HotspotsArgs {
+    /// Directory to scan
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+
+    /// How far back to count commits (e.g., 30d, 90d, 1y)
+    #[arg(long, default_value = "90d")]
+    pub since: String,
+
+    /// Show only the top N files
+    #[arg(long, default_value = "20")]
+    pub top: usize,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value = "terminal")]
+    pub format: HotspotFormat,
+
+    /// Output file (if not specified, writes to stdout)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Path to custom config file
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct BaselineArgs {
+    #[command(subcommand)]
+    pub command: BaselineCommand,
+}
+
+#[derive(Parser, Debug)]
+pub enum BaselineCommand {
+    /// Write a baseline accepting every current marker
+    Create {
+        #[command(flatten)]
+        options: BaselineOptions,
+
+        /// Overwrite an existing baseline
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Add current markers that aren't in the baseline yet
+    Update {
+        #[command(flatten)]
+        options: BaselineOptions,
+    },
+
+    /// Remove baseline entries whose markers are gone
+    Prune {
+        #[command(flatten)]
+        options: BaselineOptions,
+    },
+}
+
+#[derive(Parser, Debug)]
+pub struct BaselineOptions {
+    /// Directory to scan
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+
+    /// Baseline file (defaults to .fossil-baseline.json at the repository root)
+    #[arg(short, long)]
+    pub baseline: Option<PathBuf>,
+
+    /// Path to custom config file
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct DiffArgs {
+    /// Revisions to compare: `base..head`, `base...head` (from the merge base)
+    /// or `base` (compared with HEAD)
+    #[arg(conflicts_with = "base", required_unless_present = "base")]
+    pub range: Option<String>,
+
+    /// Base revision to compare HEAD against
+    #[arg(long)]
+    pub base: Option<String>,
+
+    /// Repository (or a directory inside it)
+    #[arg(long, default_value = ".")]
+    pub path: PathBuf,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value = "terminal")]
+    pub format: OutputFormat,
+
+    /// Output file (if not specified, writes to stdout)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Columns for CSV and TSV output, after a leading `change` column
+    #[arg(long, value_enum, value_delimiter = ',', default_value = DEFAULT_COLUMNS)]
+    pub columns: Vec<Column>,
+
+    /// Path to custom config file
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct CacheArgs {
+    #[command(subcommand)]
+    pub command: CacheCommand,
+}
+
+#[derive(Parser, Debug)]
+pub enum CacheCommand {
+    /// Remove all cached blame data
+    Clear {
+        /// Repository (or a directory inside it) whose cache to clear
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+}
+
+#[derive(Parser, Debug)]
+pub struct HistoryArgs {
+    #[command(subcommand)]
+    pub command: HistoryCommand,
+}
+
+#[derive(Parser, Debug)]
+pub enum HistoryCommand {
+    /// Start recording snapshots in .fossil/history
+    Init {
+        /// Repository (or a directory inside it) to track
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+
+    /// Scan and record a snapshot keyed by today's date and HEAD
+    Snapshot {
+        /// Directory to scan
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Path to custom config file
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+    },
+
+    /// Record snapshots of past commits by scanning them from git objects
+    Backfill {
+        /// Directory to scan
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// How far back to go (e.g., 6m, 1y)
+        #[arg(long, default_value = "1y")]
+        since: String,
+
+        /// Interval between snapshots (e.g., 1w, 30d)
+        #[arg(long, default_value = "1w")]
+        every: String,
+
+        /// Path to custom config file
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+    },
+
+    /// Show the debt trend across recorded snapshots
+    Show {
+        /// Repository (or a directory inside it) whose history to show
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Show only the N most recent snapshots
+        #[arg(long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Compare two snapshots (by date, commit, snapshot id or `latest`)
+    Compare {
+        /// Earlier snapshot
+        from: String,
+
+        /// Later snapshot
+        #[arg(default_value = "latest")]
+        to: String,
+
+        /// Repository (or a directory inside it) whose history to use
+        #[arg(long, default_value = ".")]
+        path: PathBuf,
+    },
 }
 
 #[derive(Parser, Debug)]
@@ -29,6 +237,10 @@ pub struct ScanArgs {
     #[arg(short, long)]
     pub output: Option<PathBuf>,
 
+    /// Columns for CSV and TSV output, e.g. `type,file,line,description`
+    #[arg(long, value_enum, value_delimiter = ',', default_value = DEFAULT_COLUMNS)]
+    pub columns: Vec<Column>,
+
     /// Filter: only show markers older than specified age (e.g., "30d", "6m", "1y")
     #[arg(long)]
     pub older_than: Option<String>,
@@ -37,15 +249,80 @@ pub struct ScanArgs {
     #[arg(long)]
     pub author: Option<String>,
 
+    /// Filter: only show markers assigned to someone, e.g. `TODO(alice):`
+    #[arg(long)]
+    pub assignee: Option<String>,
+
     /// Filter: only show markers of specific type (TODO, FIXME, etc.)
     #[arg(short = 't', long = "type")]
     pub marker_type: Option<String>,
 
+    /// Filter: only show markers of a severity (critical, high, medium, low, info)
+    #[arg(long)]
+    pub severity: Option<Severity>,
+
+    /// Filter: only show markers at or above a severity
+    #[arg(long, conflicts_with = "severity")]
+    pub min_severity: Option<Severity>,
+
     /// Path to custom config file
     #[arg(short, long)]
     pub config: Option<PathBuf>,
 
-    /// Show only the top N oldest markers
+    /// Number of files to git blame in parallel (overrides config; 0 = one per CPU)
+    #[arg(long)]
+    pub blame_workers: Option<usize>,
+
+    /// Skip copy/move detection in git blame (much faster on large repositories)
+    #[arg(long)]
+    pub no_track_copies: bool,
+
+    /// Don't read or write the blame cache in .fossil/cache
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// Find the commit that first introduced each marker and measure age from it
+    #[arg(long)]
+    pub origin: bool,
+
+    /// Multiply scores by each file's recent commit count (overrides config)
+    #[arg(long)]
+    pub churn: bool,
+
+    /// Filter: only show markers scoring at least this much
+    #[arg(long)]
+    pub min_score: Option<f64>,
+
+    /// Order of markers, and of the top markers list: age, file, author, type
+    /// or score [default: age]
+    #[arg(long)]
+    pub sort_by: Option<SortKey>,
+
+    /// Sort direction, asc or desc [default: desc for age and score, asc
+    /// otherwise]
+    #[arg(long)]
+    pub sort_order: Option<SortOrder>,
+
+    /// Summarize markers by file, dir, author, type, severity or assignee,
+    /// nested in order, e.g. `dir,type` (terminal, markdown and JSON output,
+    /// and templates)
+    #[arg(long, value_delimiter = ',')]
+    pub group_by: Vec<GroupKey>,
+
+    /// Only report markers that aren't in this baseline file
+    #[arg(long)]
+    pub baseline: Option<PathBuf>,
+
+    /// Lowest severity reported as a failure in JUnit output; markers below
+    /// it are reported as skipped
+    #[arg(long, default_value = "info")]
+    pub junit_fail_on: Severity,
+
+    /// How to print `[policy]` violations (written to stderr)
+    #[arg(long, value_enum, default_value = "text")]
+    pub policy_format: PolicyFormat,
+
+    /// Show the first N markers in sort order
     #[arg(long, default_value = "10")]
     pub top: usize,
 
@@ -53,6 +330,11 @@ pub struct ScanArgs {
     #[arg(long)]
     pub count_only: bool,
 
+    /// Render the report with a Tera template file instead of a format;
+    /// `builtin:terminal` and `builtin:markdown` reproduce those formats
+    #[arg(long, conflicts_with_all = ["format", "count_only"])]
+    pub template: Option<String>,
+
     /// Enable verbose output
     #[arg(short, long)]
     pub verbose: bool,
@@ -66,4 +348,90 @@ pub enum OutputFormat {
     Markdown,
     /// JSON format
     Json,
+    /// SARIF 2.1.0 for code scanning tools and SARIF viewers
+    Sarif,
+    /// Comma-separated values, one row per marker
+    Csv,
+    /// Tab-separated values, one row per marker
+    Tsv,
+    /// Self-contained interactive HTML page
+    Html,
+    /// JUnit XML: a test suite per file and a test case per marker
+    Junit,
+    /// Code Climate issues, as used by GitLab code quality reports
+    Codeclimate,
+    /// Newline-delimited JSON, one marker per line and a final summary line,
+    /// streamed as the scan runs
+    Ndjson,
+}
+
+/// Columns written when `--columns` isn't given
+const DEFAULT_COLUMNS: &str = "type,file,line,author,email,age_days,commit,description";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Column {
+    /// Marker type, e.g. TODO
+    Type,
+    /// Marker severity
+    Severity,
+    /// File path
+    File,
+    /// Line number
+    Line,
+    /// Author of the line (git blame)
+    Author,
+    /// Author email
+    Email,
+    /// Age in days
+    #[value(name = "age_days")]
+    AgeDays,
+    /// Commit that last touched the line
+    Commit,
+    /// Assignee, e.g. `alice` in `TODO(alice):`
+    Assignee,
+    /// Debt score
+    Score,
+    /// Marker description
+    Description,
+    /// Full source line
+    #[value(name = "line_content")]
+    LineContent,
+}
+
+impl Column {
+    /// Header name, as accepted by `--columns`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Column::Type => "type",
+            Column::Severity => "severity",
+            Column::File => "file",
+            Column::Line => "line",
+            Column::Author => "author",
+            Column::Email => "email",
+            Column::AgeDays => "age_days",
+            Column::Commit => "commit",
+            Column::Assignee => "assignee",
+            Column::Score => "score",
+            Column::Description => "description",
+            Column::LineContent => "line_content",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum HotspotFormat {
+    /// Ranked table for terminal
+    Terminal,
+    /// JSON format
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum PolicyFormat {
+    /// One `path:line: rule: message` line per violation
+    Text,
+    /// GitHub Actions workflow commands that annotate the build
+    Github,
+    /// JSON object with a `violations` array
+    Json,
 }
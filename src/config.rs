@@ -19,19 +19,18 @@ pub fn load_config(custom_path: Option<&Path>) -> Result<Config> {
 
     // Try current directory
     let current_config = PathBuf::from(".fossilrc");
-    if current_config.exists() {
-        if let Ok(config) = load_config_from_file(&current_config) {
-            return Ok(config);
-        }
+    if current_config.exists()
+        && let Ok(config) = load_config_from_file(&current_config)
+    {
+        return Ok(config);
     }
 
     // Try home directory
-    if let Some(home_config) = get_home_config_path() {
-        if home_config.exists() {
-            if let Ok(config) = load_config_from_file(&home_config) {
-                return Ok(config);
-            }
-        }
+    if let Some(home_config) = get_home_config_path()
+        && home_config.exists()
+        && let Ok(config) = load_config_from_file(&home_config)
+    {
+        return Ok(config);
     }
 
     // Fall back to defaults
@@ -67,6 +66,7 @@ pub fn save_config(config: &Config, path: &Path) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::{CodeMarkerScope, Severity};
     use std::io::Write;
     use tempfile::NamedTempFile;
 
@@ -87,7 +87,14 @@ context_lines = 3
 
 [severity]
 FIXME = "high"
-TODO = "low"
+todo = "low"
+
+[blame]
+workers = 4
+track_copies = false
+cache = false
+origin = true
+ignore_revs = ["abc1234"]
 "#;
         temp_file.write_all(config_content.as_bytes()).unwrap();
 
@@ -95,18 +102,91 @@ TODO = "low"
         assert_eq!(config.markers.len(), 3);
         assert!(config.markers.contains(&"CUSTOM".to_string()));
         assert_eq!(config.context_lines, 3);
-        assert_eq!(config.severity.get("FIXME"), Some(&"high".to_string()));
+        assert_eq!(config.severity.get("FIXME"), Some(&Severity::High));
+        assert_eq!(config.severity_of("todo"), Severity::Low);
+        assert_eq!(config.severity_of("CUSTOM"), Severity::Medium);
+        // Built-in entries not overridden are kept
+        assert_eq!(config.severity_of("NOTE"), Severity::Info);
+        assert_eq!(config.severity.len(), Config::default().severity.len());
+        assert_eq!(config.blame.workers, 4);
+        assert!(!config.blame.track_copies);
+        assert!(!config.blame.cache);
+        assert!(config.blame.origin);
+        assert_eq!(config.blame.ignore_revs, vec!["abc1234".to_string()]);
+    }
+
+    #[test]
+    fn test_load_code_markers() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        let config_content = r#"
+[[code_markers]]
+name = "dbg!"
+pattern = '\bdbg!\('
+extensions = ["rs"]
+
+[[code_markers]]
+name = "nolint"
+pattern = "NOLINT"
+scope = "comment"
+"#;
+        temp_file.write_all(config_content.as_bytes()).unwrap();
+
+        let config = load_config_from_file(temp_file.path()).unwrap();
+        assert_eq!(config.code_markers.len(), 2);
+        assert_eq!(config.code_markers[0].name, "dbg!");
+        assert_eq!(config.code_markers[0].scope, CodeMarkerScope::Code);
+        assert_eq!(config.code_markers[1].scope, CodeMarkerScope::Comment);
+        assert!(config.code_markers[1].extensions.is_empty());
+    }
+
+    #[test]
+    fn test_load_policy() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        let config_content = r#"
+[policy]
+max_total = 100
+require_assignee = true
+
+[policy.max_per_type]
+HACK = 0
+
+[policy.max_age]
+high = "90d"
+
+[[policy.forbidden]]
+types = ["HACK", "XXX"]
+paths = ["src/core/**"]
+"#;
+        temp_file.write_all(config_content.as_bytes()).unwrap();
+
+        let config = load_config_from_file(temp_file.path()).unwrap();
+        assert_eq!(config.policy.max_total, Some(100));
+        assert!(config.policy.require_assignee);
+        assert_eq!(config.policy.max_per_type.get("HACK"), Some(&0));
+        assert_eq!(
+            config.policy.max_age.get(&Severity::High),
+            Some(&"90d".to_string())
+        );
+        assert_eq!(config.policy.forbidden[0].paths, vec!["src/core/**"]);
     }
 
     #[test]
     fn test_save_and_load_config() {
         let temp_file = NamedTempFile::new().unwrap();
-        let config = Config::default();
+        let mut config = Config::default();
+        config
+            .policy
+            .max_age
+            .insert(Severity::High, "90d".to_string());
 
         save_config(&config, temp_file.path()).unwrap();
         let loaded = load_config_from_file(temp_file.path()).unwrap();
 
         assert_eq!(config.markers, loaded.markers);
         assert_eq!(config.context_lines, loaded.context_lines);
+        assert_eq!(config.code_markers, loaded.code_markers);
+        assert_eq!(config.blame, loaded.blame);
+        assert_eq!(config.severity, loaded.severity);
+        assert_eq!(config.policy, loaded.policy);
     }
 }
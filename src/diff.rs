@@ -0,0 +1,387 @@
+use crate::fingerprint;
+use crate::git;
+use crate::models::{Config, DebtMarker};
+use crate::scanner;
+use anyhow::{Context, Result};
+use git2::{Commit, Repository};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+
+/// A marker present in both versions, but in a different file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MovedMarker {
+    /// File the marker was in at the base revision
+    pub from_path: PathBuf,
+
+    /// Line the marker was on at the base revision
+    pub from_line: usize,
+
+    /// The marker at the head revision
+    pub marker: DebtMarker,
+}
+
+/// Debt markers added, removed and moved between two revisions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarkerDiff {
+    /// Base revision as given, e.g. `main`
+    pub base: String,
+
+    /// Head revision as given, e.g. `HEAD`
+    pub head: String,
+
+    /// Commit the base revision resolved to
+    pub base_commit: String,
+
+    /// Commit the head revision resolved to
+    pub head_commit: String,
+
+    /// Markers only present at the head revision
+    pub added: Vec<DebtMarker>,
+
+    /// Markers only present at the base revision
+    pub removed: Vec<DebtMarker>,
+
+    /// Markers that moved to another file
+    pub moved: Vec<MovedMarker>,
+
+    /// Number of markers present in the same file in both revisions
+    pub unchanged: usize,
+}
+
+/// Split a revision range into base and head
+///
+/// `base..head` compares the two revisions, `base...head` compares `head`
+/// with its merge base with `base`, and a single revision is compared with
+/// `HEAD`. Returns `(base, head, use_merge_base)`.
+pub fn parse_range(range: &str) -> (String, String, bool) {
+    let (base, head, merge_base) = if let Some((base, head)) = range.split_once("...") {
+        (base, head, true)
+    } else if let Some((base, head)) = range.split_once("..") {
+        (base, head, false)
+    } else {
+        (range, "HEAD", false)
+    };
+
+    // Like git, an empty side means HEAD
+    let side = |rev: &str| {
+        if rev.is_empty() {
+            "HEAD".to_string()
+        } else {
+            rev.to_string()
+        }
+    };
+    (side(base), side(head), merge_base)
+}
+
+fn resolve_commit<'r>(repo: &'r Repository, rev: &str) -> Result<Commit<'r>> {
+    repo.revparse_single(rev)
+        .and_then(|object| object.peel_to_commit())
+        .with_context(|| format!("Unknown revision: {}", rev))
+}
+
+/// Scan two revisions straight from git objects and compare their markers
+pub fn diff_revisions(
+    repo: &Repository,
+    base: &str,
+    head: &str,
+    merge_base: bool,
+    config: &Config,
+) -> Result<MarkerDiff> {
+    let head_commit = resolve_commit(repo, head)?;
+    let mut base_commit = resolve_commit(repo, base)?;
+    if merge_base {
+        let id = repo
+            .merge_base(base_commit.id(), head_commit.id())
+            .with_context(|| format!("No merge base between {} and {}", base, head))?;
+        base_commit = repo.find_commit(id)?;
+    }
+
+    let base_tree = base_commit.tree()?;
+    let head_tree = head_commit.tree()?;
+    let root = Path::new("");
+    let mut base_markers = scanner::scan_tree(repo, &base_tree, root, root, config)?;
+    let head_markers = scanner::scan_tree(repo, &head_tree, root, root, config)?;
+
+    // Markers in renamed files keep their fingerprint under the new path
+    let renames = git::detect_renames(repo, &base_tree, &head_tree)?;
+    for marker in &mut base_markers {
+        if let Some(new_path) = renames.get(&marker.file_path) {
+            marker.fingerprint = fingerprint::fingerprint(marker, new_path);
+        }
+    }
+
+    let (added, removed, moved, unchanged) = match_markers(base_markers, head_markers);
+
+    Ok(MarkerDiff {
+        base: base.to_string(),
+        head: head.to_string(),
+        base_commit: base_commit.id().to_string(),
+        head_commit: head_commit.id().to_string(),
+        added,
+        removed,
+        moved,
+        unchanged,
+    })
+}
+
+/// What identifies a marker across edits: its type and whitespace-normalized
+/// text, which survive code being inserted above it
+fn marker_key(marker: &DebtMarker) -> (String, String) {
+    let text = if marker.description.is_empty() {
+        &marker.line_content
+    } else {
+        &marker.description
+    };
+    (
+        marker.marker_type.clone(),
+        text.split_whitespace().collect::<Vec<_>>().join(" "),
+    )
+}
+
+fn sort_markers(markers: &mut [DebtMarker]) {
+    markers.sort_by(|a, b| {
+        a.file_path
+            .cmp(&b.file_path)
+            .then(a.line_number.cmp(&b.line_number))
+    });
+}
+
+/// Pair up markers between two versions
+///
+/// Markers are first paired by fingerprint. Remaining markers with the same
+/// key in the same file are unchanged (their context was edited), and then
+/// markers with the same key in another file moved. A marker counts as
+/// unchanged when it stays in the same file, even if its line moved.
+/// Returns `(added, removed, moved, unchanged_count)`.
+fn match_markers(
+    mut base: Vec<DebtMarker>,
+    mut head: Vec<DebtMarker>,
+) -> (Vec<DebtMarker>, Vec<DebtMarker>, Vec<MovedMarker>, usize) {
+    sort_markers(&mut base);
+    sort_markers(&mut head);
+
+    let mut unmatched_base = vec![true; base.len()];
+    let mut unchanged = 0;
+    let mut moved = Vec::new();
+
+    // Same fingerprint, possibly in a renamed file
+    let mut by_fingerprint: HashMap<&str, VecDeque<usize>> = HashMap::new();
+    for (idx, marker) in base.iter().enumerate() {
+        if !marker.fingerprint.is_empty() {
+            by_fingerprint
+                .entry(marker.fingerprint.as_str())
+                .or_default()
+                .push_back(idx);
+        }
+    }
+
+    let mut remaining_head = Vec::new();
+    for marker in head.drain(..) {
+        let matched = by_fingerprint
+            .get_mut(marker.fingerprint.as_str())
+            .and_then(VecDeque::pop_front);
+        match matched {
+            Some(idx) if base[idx].file_path == marker.file_path => {
+                unmatched_base[idx] = false;
+                unchanged += 1;
+            }
+            Some(idx) => {
+                unmatched_base[idx] = false;
+                moved.push(MovedMarker {
+                    from_path: base[idx].file_path.clone(),
+                    from_line: base[idx].line_number,
+                    marker,
+                });
+            }
+            None => remaining_head.push(marker),
+        }
+    }
+
+    // Same file, same key
+    let mut by_file: HashMap<(PathBuf, (String, String)), VecDeque<usize>> = HashMap::new();
+    for (idx, marker) in base.iter().enumerate() {
+        if unmatched_base[idx] {
+            by_file
+                .entry((marker.file_path.clone(), marker_key(marker)))
+                .or_default()
+                .push_back(idx);
+        }
+    }
+
+    let mut remaining = Vec::new();
+    for marker in remaining_head {
+        let matched = by_file
+            .get_mut(&(marker.file_path.clone(), marker_key(&marker)))
+            .and_then(VecDeque::pop_front);
+        match matched {
+            Some(idx) => {
+                unmatched_base[idx] = false;
+                unchanged += 1;
+            }
+            None => remaining.push(marker),
+        }
+    }
+
+    // Same key in another file
+    let mut by_key: HashMap<(String, String), VecDeque<usize>> = HashMap::new();
+    for (idx, marker) in base.iter().enumerate() {
+        if unmatched_base[idx] {
+            by_key.entry(marker_key(marker)).or_default().push_back(idx);
+        }
+    }
+
+    let mut added = Vec::new();
+    for marker in remaining {
+        match by_key
+            .get_mut(&marker_key(&marker))
+            .and_then(VecDeque::pop_front)
+        {
+            Some(idx) => {
+                unmatched_base[idx] = false;
+                moved.push(MovedMarker {
+                    from_path: base[idx].file_path.clone(),
+                    from_line: base[idx].line_number,
+                    marker,
+                });
+            }
+            None => added.push(marker),
+        }
+    }
+
+    let removed = base
+        .into_iter()
+        .zip(unmatched_base)
+        .filter_map(|(marker, unmatched)| unmatched.then_some(marker))
+        .collect();
+
+    (added, removed, moved, unchanged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn marker(marker_type: &str, file: &str, line: usize, description: &str) -> DebtMarker {
+        DebtMarker {
+            line_content: format!("// {}: {}", marker_type, description),
+            description: description.to_string(),
+            ..DebtMarker::test(file, line, marker_type)
+        }
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(
+            parse_range("main..HEAD"),
+            ("main".to_string(), "HEAD".to_string(), false)
+        );
+        assert_eq!(
+            parse_range("main...feature"),
+            ("main".to_string(), "feature".to_string(), true)
+        );
+        assert_eq!(
+            parse_range("main.."),
+            ("main".to_string(), "HEAD".to_string(), false)
+        );
+        assert_eq!(
+            parse_range("v1.0"),
+            ("v1.0".to_string(), "HEAD".to_string(), false)
+        );
+    }
+
+    #[test]
+    fn test_match_markers() {
+        let base = vec![
+            marker("TODO", "a.rs", 10, "shifted down"),
+            marker("TODO", "a.rs", 20, "moves away"),
+            marker("FIXME", "b.rs", 1, "gets fixed"),
+            marker("TODO", "c.rs", 1, "dup"),
+            marker("TODO", "c.rs", 5, "dup"),
+        ];
+        let head = vec![
+            marker("TODO", "a.rs", 14, "shifted  down"),
+            marker("TODO", "d.rs", 3, "moves away"),
+            marker("HACK", "b.rs", 1, "gets fixed"),
+            marker("TODO", "c.rs", 2, "dup"),
+        ];
+
+        let (added, removed, moved, unchanged) = match_markers(base, head);
+
+        assert_eq!(unchanged, 2);
+        assert_eq!(added.len(), 1);
+        assert_eq!(added[0].marker_type, "HACK");
+
+        let removed: Vec<_> = removed
+            .iter()
+            .map(|m| (m.marker_type.as_str(), m.line_number))
+            .collect();
+        assert_eq!(removed, vec![("FIXME", 1), ("TODO", 5)]);
+
+        assert_eq!(moved.len(), 1);
+        assert_eq!(moved[0].from_path, PathBuf::from("a.rs"));
+        assert_eq!(moved[0].from_line, 20);
+        assert_eq!(moved[0].marker.file_path, PathBuf::from("d.rs"));
+    }
+
+    #[test]
+    fn test_diff_revisions() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(root)
+                .output()
+                .unwrap();
+        };
+
+        git(&["init", "-b", "main"]);
+        fs::write(root.join("lib.rs"), "// TODO: keep\n// FIXME: remove\n").unwrap();
+        git(&["add", "-A"]);
+        git(&["commit", "-m", "Base"]);
+
+        git(&["checkout", "-b", "feature"]);
+        fs::write(root.join("lib.rs"), "fn a() {}\n// TODO: keep\n").unwrap();
+        fs::write(root.join("new.rs"), "// HACK: added\n").unwrap();
+        git(&["add", "-A"]);
+        git(&["commit", "-m", "Feature"]);
+
+        let repo = Repository::open(root).unwrap();
+        let diff = diff_revisions(&repo, "main", "HEAD", false, &Config::default()).unwrap();
+        assert!(diff.added.iter().all(|m| m.fingerprint.len() == 16));
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].file_path, PathBuf::from("new.rs"));
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].marker_type, "FIXME");
+
+        // Debt added on main after branching doesn't show up with `...`
+        git(&["checkout", "main"]);
+        fs::write(root.join("main.rs"), "// XXX: on main\n").unwrap();
+        git(&["add", "-A"]);
+        git(&["commit", "-m", "Main"]);
+
+        let diff = diff_revisions(&repo, "main", "feature", true, &Config::default()).unwrap();
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.removed.len(), 1);
+
+        let diff = diff_revisions(&repo, "main", "feature", false, &Config::default()).unwrap();
+        assert_eq!(diff.removed.len(), 2);
+
+        // Markers in a renamed file are matched by fingerprint
+        git(&["checkout", "feature"]);
+        git(&["mv", "lib.rs", "renamed.rs"]);
+        git(&["commit", "-m", "Rename"]);
+        let diff = diff_revisions(&repo, "HEAD~1", "HEAD", false, &Config::default()).unwrap();
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert_eq!(diff.moved.len(), 1);
+        assert_eq!(diff.moved[0].from_path, PathBuf::from("lib.rs"));
+
+        assert!(diff_revisions(&repo, "nope", "HEAD", false, &Config::default()).is_err());
+    }
+}
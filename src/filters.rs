@@ -1,4 +1,4 @@
-use crate::models::DebtMarker;
+use crate::models::{DebtMarker, Severity};
 use anyhow::{Context, Result, anyhow};
 use std::time::Duration;
 
@@ -48,8 +48,39 @@ pub fn filter_by_type(markers: Vec<DebtMarker>, marker_type: &str) -> Vec<DebtMa
         .collect()
 }
 
+/// Filter markers by assignee (case-insensitive partial match)
+pub fn filter_by_assignee(markers: Vec<DebtMarker>, assignee: &str) -> Vec<DebtMarker> {
+    let assignee_lower = assignee.to_lowercase();
+
+    markers
+        .into_iter()
+        .filter(|marker| {
+            marker
+                .assignee
+                .as_ref()
+                .is_some_and(|a| a.to_lowercase().contains(&assignee_lower))
+        })
+        .collect()
+}
+
+/// Filter markers by exact severity
+pub fn filter_by_severity(markers: Vec<DebtMarker>, severity: Severity) -> Vec<DebtMarker> {
+    markers
+        .into_iter()
+        .filter(|marker| marker.severity == severity)
+        .collect()
+}
+
+/// Filter markers at or above a severity
+pub fn filter_by_min_severity(markers: Vec<DebtMarker>, min: Severity) -> Vec<DebtMarker> {
+    markers
+        .into_iter()
+        .filter(|marker| marker.severity >= min)
+        .collect()
+}
+
 /// Parse duration string like "30d", "6m", "1y" into Duration
-fn parse_duration(s: &str) -> Result<Duration> {
+pub fn parse_duration(s: &str) -> Result<Duration> {
     if s.is_empty() {
         return Err(anyhow!("Empty duration string"));
     }
@@ -81,24 +112,11 @@ fn parse_duration(s: &str) -> Result<Duration> {
 mod tests {
     use super::*;
     use crate::models::GitBlameInfo;
-    use chrono::Utc;
-    use std::path::PathBuf;
 
     fn create_test_marker(marker_type: &str, age_days: i64, author: &str) -> DebtMarker {
         DebtMarker {
-            marker_type: marker_type.to_string(),
-            file_path: PathBuf::from("test.rs"),
-            line_number: 1,
-            line_content: format!("// {}: test", marker_type),
-            context_before: vec![],
-            context_after: vec![],
-            git_info: Some(GitBlameInfo {
-                author: author.to_string(),
-                author_email: format!("{}@example.com", author.to_lowercase()),
-                commit_hash: "abc123".to_string(),
-                commit_time: Utc::now(),
-                age_days,
-            }),
+            git_info: Some(GitBlameInfo::test(author, age_days)),
+            ..DebtMarker::test("test.rs", 1, marker_type)
         }
     }
 
@@ -179,6 +197,34 @@ mod tests {
         assert_eq!(filtered2[0].marker_type, "FIXME");
     }
 
+    #[test]
+    fn test_filter_by_assignee() {
+        let mut alice = create_test_marker("TODO", 10, "Alice");
+        alice.assignee = Some("alice".to_string());
+        let unassigned = create_test_marker("TODO", 20, "Bob");
+
+        let filtered = filter_by_assignee(vec![alice, unassigned], "ALI");
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].assignee.as_deref(), Some("alice"));
+    }
+
+    #[test]
+    fn test_filter_by_severity() {
+        let mut critical = create_test_marker("BUG", 10, "Alice");
+        critical.severity = Severity::Critical;
+        let mut low = create_test_marker("NOTE", 20, "Bob");
+        low.severity = Severity::Low;
+        let markers = vec![critical, low, create_test_marker("TODO", 30, "Charlie")];
+
+        let filtered = filter_by_severity(markers.clone(), Severity::Medium);
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].marker_type, "TODO");
+
+        let filtered = filter_by_min_severity(markers, Severity::Medium);
+        let types: Vec<_> = filtered.iter().map(|m| m.marker_type.as_str()).collect();
+        assert_eq!(types, vec!["BUG", "TODO"]);
+    }
+
     #[test]
     fn test_filter_without_git_info() {
         let mut marker = create_test_marker("TODO", 100, "Alice");
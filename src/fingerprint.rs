@@ -0,0 +1,155 @@
+use crate::models::DebtMarker;
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path, PathBuf};
+
+/// Non-blank context lines on each side of a marker that contribute to its
+/// fingerprint; more would make it change whenever nearby code is edited
+const FINGERPRINT_CONTEXT: usize = 2;
+
+/// Collapse runs of whitespace so reindenting doesn't change a fingerprint
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Path with `.` components dropped and `/` separators, so a file hashes the
+/// same however the scan path was written and on every platform
+fn normalize_path(path: &Path) -> String {
+    path.components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Compute a marker's fingerprint as if it were in `path`
+///
+/// The fingerprint hashes the marker type, `path` (relative to the repository
+/// root), the whitespace-normalized marker text and the nearest non-blank
+/// context lines. It doesn't depend on the line number, so it survives code
+/// being added or removed elsewhere in the file. Pass the file's new path to
+/// follow a rename.
+pub fn fingerprint(marker: &DebtMarker, path: &Path) -> String {
+    let text = if marker.description.is_empty() {
+        &marker.line_content
+    } else {
+        &marker.description
+    };
+
+    let before = marker
+        .context_before
+        .iter()
+        .rev()
+        .map(|line| normalize(line))
+        .filter(|line| !line.is_empty())
+        .take(FINGERPRINT_CONTEXT);
+    let after = marker
+        .context_after
+        .iter()
+        .map(|line| normalize(line))
+        .filter(|line| !line.is_empty())
+        .take(FINGERPRINT_CONTEXT);
+
+    let mut hasher = Sha256::new();
+    let fields = [
+        marker.marker_type.clone(),
+        normalize_path(path),
+        normalize(text),
+    ];
+    for field in fields.into_iter().chain(before).chain(after) {
+        hasher.update(field.as_bytes());
+        hasher.update([0]); // Field separator
+    }
+
+    hasher.finalize()[..8]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Path of a marker found under `scan_path` relative to the repository root
+///
+/// `prefix` is the location of `scan_path` relative to the repository root
+/// (empty outside a repository).
+pub fn repo_path(marker: &DebtMarker, scan_path: &Path, prefix: &Path) -> PathBuf {
+    repo_relative(&marker.file_path, scan_path, prefix)
+}
+
+/// A file path from a scan of `scan_path` made relative to the repository
+/// root (see [`repo_path`])
+pub fn repo_relative(file_path: &Path, scan_path: &Path, prefix: &Path) -> PathBuf {
+    let relative = file_path.strip_prefix(scan_path).unwrap_or(file_path);
+    PathBuf::from(normalize_path(&prefix.join(relative)))
+}
+
+/// Fill in `fingerprint` for markers found under `scan_path`
+///
+/// Paths are taken relative to the repository root (see [`repo_path`]), so
+/// fingerprints don't depend on which directory was scanned.
+pub fn assign_fingerprints(markers: &mut [DebtMarker], scan_path: &Path, prefix: &Path) {
+    for marker in markers {
+        marker.fingerprint = fingerprint(marker, &repo_path(marker, scan_path, prefix));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marker(file: &str, line: usize, description: &str) -> DebtMarker {
+        DebtMarker {
+            line_content: format!("// TODO: {}", description),
+            description: description.to_string(),
+            context_before: vec!["fn main() {".to_string(), "".to_string()],
+            context_after: vec!["    run();".to_string()],
+            ..DebtMarker::test(file, line, "TODO")
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_is_stable_across_edits() {
+        let original = marker("src/lib.rs", 10, "handle errors");
+        let fp = fingerprint(&original, Path::new("src/lib.rs"));
+        assert_eq!(fp.len(), 16);
+
+        // Moved down the file and reindented
+        let mut moved = marker("src/lib.rs", 42, "handle   errors");
+        moved.context_before = vec!["  fn main() {".to_string()];
+        moved.context_after = vec!["run();".to_string(), "".to_string()];
+        assert_eq!(fingerprint(&moved, Path::new("./src/lib.rs")), fp);
+
+        // Different text, type, file or context
+        let other = marker("src/lib.rs", 10, "handle all errors");
+        assert_ne!(fingerprint(&other, Path::new("src/lib.rs")), fp);
+
+        let mut fixme = original.clone();
+        fixme.marker_type = "FIXME".to_string();
+        assert_ne!(fingerprint(&fixme, Path::new("src/lib.rs")), fp);
+
+        assert_ne!(fingerprint(&original, Path::new("src/main.rs")), fp);
+
+        let mut context = original.clone();
+        context.context_after = vec!["    stop();".to_string()];
+        assert_ne!(fingerprint(&context, Path::new("src/lib.rs")), fp);
+    }
+
+    #[test]
+    fn test_assign_fingerprints_uses_repository_path() {
+        // Scanning `src` from the repository root and `.` from inside `src`
+        // give the same fingerprint
+        let mut from_root = vec![marker("src/lib.rs", 1, "x")];
+        assign_fingerprints(&mut from_root, Path::new("src"), Path::new("src"));
+
+        let mut from_src = vec![marker("./lib.rs", 1, "x")];
+        assign_fingerprints(&mut from_src, Path::new("."), Path::new("src"));
+
+        assert_eq!(from_root[0].fingerprint, from_src[0].fingerprint);
+        assert_eq!(
+            repo_path(&from_src[0], Path::new("."), Path::new("src")),
+            PathBuf::from("src/lib.rs")
+        );
+        assert_eq!(
+            from_root[0].fingerprint,
+            fingerprint(&from_root[0], Path::new("src/lib.rs"))
+        );
+    }
+}
@@ -1,9 +1,18 @@
-use crate::models::{DebtMarker, GitBlameInfo};
+use crate::cache::{self, BlameCache, BlameCacheKey, BlameHunk};
+use crate::models::{BlameConfig, DebtMarker, GitBlameInfo};
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
-use git2::{BlameOptions, Repository};
-use std::collections::HashMap;
+use git2::{
+    BlameOptions, Commit, Delta, DiffFindOptions, DiffOptions, ObjectType, Oid, Patch, Repository,
+    Sort, Tree,
+};
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// File listing commits to skip when attributing lines, as used by `git blame`
+pub const IGNORE_REVS_FILE: &str = ".git-blame-ignore-revs";
 
 /// Get git repository for a given path
 pub fn get_repository(path: &Path) -> Result<Option<Repository>> {
@@ -13,56 +22,159 @@ pub fn get_repository(path: &Path) -> Result<Option<Repository>> {
     }
 }
 
-/// Get git blame information for a specific line in a file
-pub fn blame_line(
+/// Root of the repository containing `path`, falling back to `path` itself
+/// outside a repository
+pub fn repo_root(path: &Path) -> Result<PathBuf> {
+    let repo = get_repository(path)?;
+    Ok(repo
+        .as_ref()
+        .and_then(|r| r.workdir())
+        .map_or_else(|| path.to_path_buf(), Path::to_path_buf))
+}
+
+/// Location of `path` relative to the root of its repository
+///
+/// Empty at the repository root and outside a repository.
+pub fn repo_prefix(path: &Path) -> PathBuf {
+    let Ok(Some(repo)) = get_repository(path) else {
+        return PathBuf::new();
+    };
+    repo.workdir()
+        .and_then(|workdir| relative_to_workdir(workdir, path))
+        .unwrap_or_default()
+}
+
+/// Files renamed between two trees, as old path → new path
+pub fn detect_renames(
     repo: &Repository,
-    file_path: &Path,
-    line_number: usize,
-) -> Result<Option<GitBlameInfo>> {
-    // Get the file path relative to the repository root
-    let workdir = repo
-        .workdir()
-        .context("Repository has no working directory")?;
+    old_tree: &Tree,
+    new_tree: &Tree,
+) -> Result<HashMap<PathBuf, PathBuf>> {
+    let mut diff = repo
+        .diff_tree_to_tree(Some(old_tree), Some(new_tree), None)
+        .context("Failed to diff trees")?;
+    let mut opts = DiffFindOptions::new();
+    opts.renames(true);
+    diff.find_similar(Some(&mut opts))
+        .context("Failed to detect renames")?;
+
+    Ok(diff
+        .deltas()
+        .filter(|delta| delta.status() == Delta::Renamed)
+        .filter_map(|delta| {
+            Some((
+                delta.old_file().path()?.to_path_buf(),
+                delta.new_file().path()?.to_path_buf(),
+            ))
+        })
+        .collect())
+}
+
+/// Files renamed between a commit and HEAD, as old path → new path
+pub fn renames_since(repo: &Repository, rev: &str) -> Result<HashMap<PathBuf, PathBuf>> {
+    let old_tree = repo
+        .revparse_single(rev)
+        .and_then(|object| object.peel_to_tree())
+        .with_context(|| format!("Unknown revision: {}", rev))?;
+    let head_tree = repo
+        .head()
+        .and_then(|head| head.peel_to_tree())
+        .context("Repository has no HEAD commit")?;
+
+    detect_renames(repo, &old_tree, &head_tree)
+}
+
+/// Full id of the commit HEAD points at
+pub fn head_commit(repo: &Repository) -> Option<String> {
+    repo.head()
+        .ok()?
+        .peel_to_commit()
+        .ok()
+        .map(|c| c.id().to_string())
+}
 
+/// Recent changes to a file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileActivity {
+    /// Number of commits that changed the file
+    pub commits: usize,
+
+    /// Names of the authors of those commits
+    pub authors: HashSet<String>,
+}
+
+/// Commits and authors touching each file (relative to the repository root)
+/// in the last `since`
+///
+/// Merge commits are skipped so changes are counted once, on the commit that
+/// made them. A repository without commits has no activity.
+pub fn file_activity(repo: &Repository, since: Duration) -> Result<HashMap<PathBuf, FileActivity>> {
+    let cutoff = Utc::now().timestamp() - since.as_secs() as i64;
+    let mut activity: HashMap<PathBuf, FileActivity> = HashMap::new();
+
+    let mut revwalk = repo.revwalk().context("Failed to walk history")?;
+    if revwalk.push_head().is_err() {
+        return Ok(activity);
+    }
+    revwalk.set_sorting(Sort::TIME)?;
+
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        if commit.time().seconds() < cutoff {
+            break;
+        }
+        if commit.parent_count() > 1 {
+            continue;
+        }
+
+        let parent_tree = match commit.parent_count() {
+            0 => None,
+            _ => Some(commit.parent(0)?.tree()?),
+        };
+        let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+        let author = commit.author().name().unwrap_or("Unknown").to_string();
+
+        for delta in diff.deltas() {
+            if let Some(path) = delta.new_file().path().or(delta.old_file().path()) {
+                let entry = activity.entry(path.to_path_buf()).or_default();
+                entry.commits += 1;
+                entry.authors.insert(author.clone());
+            }
+        }
+    }
+
+    Ok(activity)
+}
+
+/// Convert a file path to a path relative to the repository working directory
+pub fn relative_to_workdir(workdir: &Path, file_path: &Path) -> Option<PathBuf> {
     // Canonicalize the file path to handle .. and . in the path
-    let canonical_path = file_path
-        .canonicalize()
-        .with_context(|| format!("Failed to canonicalize path: {}", file_path.display()))?;
+    let canonical_path = file_path.canonicalize().ok()?;
 
     let relative_path = canonical_path
         .strip_prefix(workdir)
         .unwrap_or(&canonical_path);
 
     // Remove leading "./" if present - git2 doesn't accept paths starting with "."
-    let relative_path_str = relative_path
-        .to_str()
-        .context("Invalid UTF-8 in file path")?;
+    let relative_path_str = relative_path.to_str()?;
     let cleaned_path = relative_path_str
         .strip_prefix("./")
         .unwrap_or(relative_path_str);
-    let relative_path = Path::new(cleaned_path);
 
-    // Create blame options
-    let mut opts = BlameOptions::new();
-    opts.track_copies_same_file(true)
-        .track_copies_same_commit_moves(true)
-        .track_copies_same_commit_copies(true);
-
-    // Run blame on the file
-    let blame = match repo.blame_file(relative_path, Some(&mut opts)) {
-        Ok(b) => b,
-        Err(_) => return Ok(None), // File might not be in git, or other error
-    };
+    Some(PathBuf::from(cleaned_path))
+}
 
-    // Get the hunk for the specific line
-    // Note: git blame uses 1-indexed lines, which matches our line_number
-    let hunk = match blame.get_line(line_number) {
-        Some(h) => h,
-        None => return Ok(None), // Line not found
-    };
+/// Build blame options; copy tracking is accurate but expensive
+fn blame_options(track_copies: bool) -> BlameOptions {
+    let mut opts = BlameOptions::new();
+    opts.track_copies_same_file(track_copies)
+        .track_copies_same_commit_moves(track_copies)
+        .track_copies_same_commit_copies(track_copies);
+    opts
+}
 
-    // Get the commit info
-    let commit_id = hunk.final_commit_id();
+/// Build blame information from the commit that last touched a line
+fn blame_info_for_commit(repo: &Repository, commit_id: Oid) -> Result<GitBlameInfo> {
     let commit = repo.find_commit(commit_id)?;
 
     // Extract author info
@@ -82,13 +194,44 @@ pub fn blame_line(
     // Get short commit hash
     let commit_hash = format!("{:.7}", commit_id);
 
-    Ok(Some(GitBlameInfo {
+    Ok(GitBlameInfo {
         author: author_name,
         author_email,
         commit_hash,
         commit_time,
         age_days,
-    }))
+        introduced_by: None,
+        introduced_at: None,
+        introduced_commit: None,
+    })
+}
+
+/// Get git blame information for a specific line in a file
+///
+/// Lines are attributed exactly as in [`enrich_markers_batch`], including
+/// ignored revisions, but without the cache or origin mode.
+pub fn blame_line(
+    repo: &Repository,
+    file_path: &Path,
+    line_number: usize,
+    config: &BlameConfig,
+) -> Result<Option<GitBlameInfo>> {
+    // Get the file path relative to the repository root
+    let workdir = repo
+        .workdir()
+        .context("Repository has no working directory")?;
+
+    let relative_path = relative_to_workdir(workdir, file_path)
+        .with_context(|| format!("Failed to resolve path: {}", file_path.display()))?;
+
+    // File might not be in git, or other error
+    let ignore_revs = load_ignore_revs(repo, &config.ignore_revs);
+    let Some(hunks) = compute_hunks(repo, &relative_path, config.track_copies, &ignore_revs) else {
+        return Ok(None);
+    };
+
+    // Note: git blame uses 1-indexed lines, which matches our line_number
+    Ok(cache::lookup_line(&hunks, line_number).cloned())
 }
 
 /// Enrich a marker with git blame information
@@ -96,127 +239,719 @@ pub fn enrich_with_git_info(
     repo: Option<&Repository>,
     file_path: &Path,
     line_number: usize,
+    config: &BlameConfig,
 ) -> Option<GitBlameInfo> {
     let repo = repo?;
 
-    blame_line(repo, file_path, line_number).unwrap_or_default()
+    blame_line(repo, file_path, line_number, config).unwrap_or_default()
 }
 
-/// Batch enrich markers with git blame information
-/// Groups markers by file and runs git blame once per file for better performance
-pub fn enrich_markers_batch(markers: &mut [DebtMarker], repo: Option<&Repository>) -> Result<()> {
-    let repo = match repo {
-        Some(r) => r,
-        None => return Ok(()), // No repository, skip enrichment
-    };
+/// Limit on chains of ignored commits followed for a single line
+const MAX_IGNORED_DEPTH: usize = 32;
 
-    // Get repository working directory once
-    let workdir = repo
-        .workdir()
-        .context("Repository has no working directory")?;
+/// Where blame found a line: a commit and the line's position in that commit
+#[derive(Debug, Clone)]
+struct LineOrigin {
+    commit: Oid,
+    path: PathBuf,
+    line: usize,
+}
 
-    // Group markers by file path
-    let mut markers_by_file: HashMap<PathBuf, Vec<usize>> = HashMap::new();
-    for (idx, marker) in markers.iter().enumerate() {
-        markers_by_file
-            .entry(marker.file_path.clone())
-            .or_default()
-            .push(idx);
-    }
+/// Blame of the file in the parent of an ignored commit
+struct ParentBlame {
+    /// Regions changed by the ignored commit as
+    /// `(old_start, old_lines, new_start, new_lines)`
+    changes: Vec<(usize, usize, usize, usize)>,
 
-    // Process each file once
-    for (file_path, marker_indices) in markers_by_file {
-        // Canonicalize and convert to relative path
+    /// Hunks of the parent's blame as `(start_line, lines, origin of start_line)`
+    hunks: Vec<(usize, usize, LineOrigin)>,
+}
 
-        // Canonicalize the file path to handle .. and . in the path
-        let canonical_path = match file_path.canonicalize() {
-            Ok(p) => p,
-            Err(_) => continue, // Skip files that can't be canonicalized
-        };
+/// Map a line of a file to the parent's version, given the changed regions
+///
+/// Unchanged lines shift past earlier changes. Modified lines map to the line
+/// at the same offset in the replaced region (or its last line), which is the
+/// same fallback `git blame --ignore-rev` uses. Lines the commit added have no
+/// counterpart.
+fn map_to_parent(changes: &[(usize, usize, usize, usize)], line: usize) -> Option<usize> {
+    let mut delta: isize = 0;
 
-        let relative_path = canonical_path
-            .strip_prefix(workdir)
-            .unwrap_or(&canonical_path);
+    for &(old_start, old_lines, new_start, new_lines) in changes {
+        if new_lines > 0 && line >= new_start && line < new_start + new_lines {
+            if old_lines == 0 {
+                return None; // Added by the commit
+            }
+            return Some(old_start + (line - new_start).min(old_lines - 1));
+        }
 
-        // Remove leading "./" if present
-        let relative_path_str = match relative_path.to_str() {
-            Some(s) => s,
-            None => continue, // Skip files with invalid UTF-8
-        };
-        let cleaned_path = relative_path_str
-            .strip_prefix("./")
-            .unwrap_or(relative_path_str);
-        let relative_path = Path::new(cleaned_path);
-
-        // Create blame options
-        let mut opts = BlameOptions::new();
-        opts.track_copies_same_file(true)
-            .track_copies_same_commit_moves(true)
-            .track_copies_same_commit_copies(true);
-
-        // Run blame once for this file
-        let blame = match repo.blame_file(relative_path, Some(&mut opts)) {
-            Ok(b) => b,
-            Err(_) => continue, // Skip files that can't be blamed
+        // Pure deletions are reported after the preceding new line
+        let before = if new_lines == 0 {
+            new_start < line
+        } else {
+            new_start + new_lines <= line
         };
+        if before {
+            delta += old_lines as isize - new_lines as isize;
+        }
+    }
 
-        // Cache blame info by line number
-        let mut blame_cache: HashMap<usize, GitBlameInfo> = HashMap::new();
+    usize::try_from(line as isize + delta).ok()
+}
 
-        // Process all markers for this file
-        for &marker_idx in &marker_indices {
-            let marker = &mut markers[marker_idx];
-            let line_number = marker.line_number;
+/// Re-attributes lines blamed on ignored commits to the commit before them
+struct IgnoredRevsBlamer<'a> {
+    repo: &'a Repository,
+    track_copies: bool,
+    ignore_revs: &'a HashSet<Oid>,
+    parents: HashMap<(Oid, PathBuf), Option<ParentBlame>>,
+}
 
-            // Check cache first
-            if let Some(git_info) = blame_cache.get(&line_number) {
-                marker.git_info = Some(git_info.clone());
-                continue;
+impl<'a> IgnoredRevsBlamer<'a> {
+    fn new(repo: &'a Repository, track_copies: bool, ignore_revs: &'a HashSet<Oid>) -> Self {
+        Self {
+            repo,
+            track_copies,
+            ignore_revs,
+            parents: HashMap::new(),
+        }
+    }
+
+    /// Follow a line past ignored commits to the commit that last changed it
+    ///
+    /// Lines that can't be traced (e.g. added by an ignored commit) stay
+    /// attributed to the ignored commit, as with `git blame`.
+    fn attribute(&mut self, origin: LineOrigin) -> Oid {
+        let mut current = origin;
+
+        for _ in 0..MAX_IGNORED_DEPTH {
+            if !self.ignore_revs.contains(&current.commit) {
+                break;
             }
 
-            // Get the hunk for this line
-            let hunk = match blame.get_line(line_number) {
+            let key = (current.commit, current.path.clone());
+            let parent = self
+                .parents
+                .entry(key)
+                .or_insert_with(|| {
+                    parent_blame(self.repo, current.commit, &current.path, self.track_copies)
+                })
+                .as_ref();
+
+            let next = parent.and_then(|parent| {
+                let line = map_to_parent(&parent.changes, current.line)?;
+                parent
+                    .hunks
+                    .iter()
+                    .find(|(start, lines, _)| line >= *start && line < start + lines)
+                    .map(|(start, _, hunk_origin)| LineOrigin {
+                        line: hunk_origin.line + (line - start),
+                        ..hunk_origin.clone()
+                    })
+            });
+
+            match next {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+
+        current.commit
+    }
+}
+
+/// Blame a file as of the first parent of `commit_id`, along with the changes
+/// `commit_id` made to it
+fn parent_blame(
+    repo: &Repository,
+    commit_id: Oid,
+    path: &Path,
+    track_copies: bool,
+) -> Option<ParentBlame> {
+    let commit = repo.find_commit(commit_id).ok()?;
+    let parent = commit.parents().next()?;
+    let parent_path = path_in_parent(repo, &commit, &parent, path)?;
+
+    let old_blob = repo.find_blob(blob_at(&parent, &parent_path)?).ok()?;
+    let new_blob = repo.find_blob(blob_at(&commit, path)?).ok()?;
+    let mut diff_opts = DiffOptions::new();
+    diff_opts.context_lines(0);
+    let patch = Patch::from_blobs(
+        &old_blob,
+        Some(&parent_path),
+        &new_blob,
+        Some(path),
+        Some(&mut diff_opts),
+    )
+    .ok()?;
+
+    let changes = (0..patch.num_hunks())
+        .filter_map(|idx| patch.hunk(idx).ok())
+        .map(|(hunk, _)| {
+            (
+                hunk.old_start() as usize,
+                hunk.old_lines() as usize,
+                hunk.new_start() as usize,
+                hunk.new_lines() as usize,
+            )
+        })
+        .collect();
+
+    let mut opts = blame_options(track_copies);
+    opts.newest_commit(parent.id());
+    let blame = repo.blame_file(&parent_path, Some(&mut opts)).ok()?;
+
+    let hunks = blame
+        .iter()
+        .map(|hunk| {
+            (
+                hunk.final_start_line(),
+                hunk.lines_in_hunk(),
+                LineOrigin {
+                    commit: hunk.final_commit_id(),
+                    path: hunk
+                        .path()
+                        .map_or_else(|| parent_path.clone(), Path::to_path_buf),
+                    line: hunk.orig_start_line(),
+                },
+            )
+        })
+        .collect();
+
+    Some(ParentBlame { changes, hunks })
+}
+
+/// Look up blame information for a commit, memoized per blame run
+fn commit_info(
+    repo: &Repository,
+    commits: &mut HashMap<Oid, GitBlameInfo>,
+    commit_id: Oid,
+) -> Option<GitBlameInfo> {
+    if let Some(info) = commits.get(&commit_id) {
+        return Some(info.clone());
+    }
+    let info = blame_info_for_commit(repo, commit_id).ok()?;
+    commits.insert(commit_id, info.clone());
+    Some(info)
+}
+
+/// Run blame on a file and attribute every line to a commit
+///
+/// Lines last changed by a commit in `ignore_revs` are attributed to the
+/// commit that changed them before it.
+fn compute_hunks(
+    repo: &Repository,
+    relative_path: &Path,
+    track_copies: bool,
+    ignore_revs: &HashSet<Oid>,
+) -> Option<Vec<BlameHunk>> {
+    let mut opts = blame_options(track_copies);
+    let blame = repo.blame_file(relative_path, Some(&mut opts)).ok()?;
+
+    // Many hunks share a commit, so look each commit up once
+    let mut commits: HashMap<Oid, GitBlameInfo> = HashMap::new();
+    let mut hunks = Vec::with_capacity(blame.len());
+    let mut blamer = IgnoredRevsBlamer::new(repo, track_copies, ignore_revs);
+
+    for hunk in blame.iter() {
+        let commit_id = hunk.final_commit_id();
+
+        if ignore_revs.contains(&commit_id) {
+            // Lines of an ignored hunk may come from different commits
+            let path = hunk
+                .path()
+                .map_or_else(|| relative_path.to_path_buf(), Path::to_path_buf);
+            for offset in 0..hunk.lines_in_hunk() {
+                let origin = blamer.attribute(LineOrigin {
+                    commit: commit_id,
+                    path: path.clone(),
+                    line: hunk.orig_start_line() + offset,
+                });
+                if let Some(info) = commit_info(repo, &mut commits, origin) {
+                    hunks.push(BlameHunk {
+                        start_line: hunk.final_start_line() + offset,
+                        lines: 1,
+                        info,
+                    });
+                }
+            }
+            continue;
+        }
+
+        let Some(info) = commit_info(repo, &mut commits, commit_id) else {
+            continue; // Skip if commit not found
+        };
+
+        hunks.push(BlameHunk {
+            start_line: hunk.final_start_line(),
+            lines: hunk.lines_in_hunk(),
+            info,
+        });
+    }
+
+    Some(hunks)
+}
+
+/// Short tag identifying a set of ignored commits, for cache keys
+fn ignore_revs_tag(ignore_revs: &HashSet<Oid>) -> Option<String> {
+    if ignore_revs.is_empty() {
+        return None;
+    }
+
+    let mut revs: Vec<String> = ignore_revs.iter().map(Oid::to_string).collect();
+    revs.sort();
+    let digest = Oid::hash_object(ObjectType::Blob, revs.join("\n").as_bytes()).ok()?;
+    Some(format!("{:.12}", digest))
+}
+
+/// Cache key for a file: the HEAD commit, its path and blob id in the HEAD
+/// tree, plus the blame settings and ignored revisions
+fn cache_key(
+    repo: &Repository,
+    relative_path: &Path,
+    config: &BlameConfig,
+    ignore_revs: &HashSet<Oid>,
+) -> Option<BlameCacheKey> {
+    let head = repo.head().ok()?.peel_to_commit().ok()?;
+    let entry = head.tree().ok()?.get_path(relative_path).ok()?;
+
+    let mut variant = if config.track_copies {
+        "copies".to_string()
+    } else {
+        "nocopies".to_string()
+    };
+    if let Some(tag) = ignore_revs_tag(ignore_revs) {
+        variant.push_str("-ignore");
+        variant.push_str(&tag);
+    }
+
+    Some(BlameCacheKey {
+        head: head.id().to_string(),
+        path: relative_path.to_path_buf(),
+        blob: entry.id().to_string(),
+        variant,
+    })
+}
+
+/// Run blame once for a file and look up each requested line
+///
+/// `lines` pairs a marker index with its line number; the result pairs each
+/// marker index with its blame information. Blame results are read from and
+/// stored in `cache` when one is given.
+fn blame_file_lines(
+    repo: &Repository,
+    workdir: &Path,
+    file_path: &Path,
+    lines: &[(usize, usize)],
+    config: &BlameConfig,
+    ignore_revs: &HashSet<Oid>,
+    cache: Option<&BlameCache>,
+) -> Vec<(usize, GitBlameInfo)> {
+    let relative_path = match relative_to_workdir(workdir, file_path) {
+        Some(p) => p,
+        None => return Vec::new(), // Skip files that can't be resolved
+    };
+
+    let key = cache.and_then(|_| cache_key(repo, &relative_path, config, ignore_revs));
+
+    let cached = match (cache, &key) {
+        (Some(cache), Some(key)) => cache.get(key),
+        _ => None,
+    };
+
+    let hunks = match cached {
+        Some(hunks) => hunks,
+        None => {
+            let hunks = match compute_hunks(repo, &relative_path, config.track_copies, ignore_revs)
+            {
                 Some(h) => h,
-                None => continue, // Skip lines not found in blame
+                None => return Vec::new(), // Skip files that can't be blamed
             };
+            if let (Some(cache), Some(key)) = (cache, &key) {
+                // A failed write only costs a re-blame next time
+                let _ = cache.put(key, &hunks);
+            }
+            hunks
+        }
+    };
 
-            // Get the commit info
-            let commit_id = hunk.final_commit_id();
-            let commit = match repo.find_commit(commit_id) {
-                Ok(c) => c,
-                Err(_) => continue, // Skip if commit not found
-            };
+    lines
+        .iter()
+        .filter_map(|&(marker_idx, line_number)| {
+            cache::lookup_line(&hunks, line_number).map(|info| (marker_idx, info.clone()))
+        })
+        .collect()
+}
+
+/// Upper bound on commits visited when searching for a marker's origin
+const MAX_ORIGIN_DEPTH: usize = 10_000;
+
+/// Collapse runs of whitespace so reindented lines still compare equal
+fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
 
-            // Extract author info
-            let author = commit.author();
-            let author_name = author.name().unwrap_or("Unknown").to_string();
-            let author_email = author.email().unwrap_or("unknown@example.com").to_string();
-
-            // Get commit time
-            let commit_time_secs = commit.time().seconds();
-            let commit_time =
-                DateTime::from_timestamp(commit_time_secs, 0).unwrap_or_else(Utc::now);
-
-            // Calculate age in days
-            let now = Utc::now();
-            let duration = now.signed_duration_since(commit_time);
-            let age_days = duration.num_days();
-
-            // Get short commit hash
-            let commit_hash = format!("{:.7}", commit_id);
-
-            let git_info = GitBlameInfo {
-                author: author_name,
-                author_email,
-                commit_hash,
-                commit_time,
-                age_days,
+/// Text identifying a marker across reformatting: its line from the marker
+/// onwards, with whitespace collapsed
+fn marker_needle(marker: &DebtMarker) -> String {
+    let line = &marker.line_content;
+    let text = line
+        .find(&marker.marker_type)
+        .map_or(line.as_str(), |start| &line[start..]);
+    normalize_whitespace(text)
+}
+
+/// Commits to skip when attributing lines
+///
+/// Combines `.git-blame-ignore-revs` at the repository root, the file named by
+/// git's `blame.ignoreRevsFile` setting and `extra` revisions from the config.
+/// Blank lines, `#` comments and revisions that don't resolve to a commit are
+/// skipped.
+pub fn load_ignore_revs(repo: &Repository, extra: &[String]) -> HashSet<Oid> {
+    let mut files = Vec::new();
+    if let Some(workdir) = repo.workdir() {
+        files.push(workdir.join(IGNORE_REVS_FILE));
+        if let Ok(configured) = repo
+            .config()
+            .and_then(|config| config.get_path("blame.ignoreRevsFile"))
+        {
+            files.push(workdir.join(configured));
+        }
+    }
+
+    let from_files: Vec<String> = files
+        .iter()
+        .filter_map(|file| std::fs::read_to_string(file).ok())
+        .flat_map(|contents| {
+            contents
+                .lines()
+                .map(|line| line.split('#').next().unwrap_or("").trim().to_string())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    from_files
+        .iter()
+        .chain(extra)
+        .map(|rev| rev.trim())
+        .filter(|rev| !rev.is_empty())
+        .filter_map(|rev| repo.revparse_single(rev).ok()?.peel_to_commit().ok())
+        .map(|commit| commit.id())
+        .collect()
+}
+
+/// Blob id of a file in a commit's tree
+fn blob_at(commit: &Commit, path: &Path) -> Option<Oid> {
+    commit
+        .tree()
+        .ok()?
+        .get_path(path)
+        .ok()
+        .map(|entry| entry.id())
+}
+
+/// Check whether a blob has a line containing `needle` (whitespace-normalized)
+fn blob_contains(repo: &Repository, blob_id: Oid, needle: &str) -> bool {
+    let Ok(blob) = repo.find_blob(blob_id) else {
+        return false;
+    };
+    String::from_utf8_lossy(blob.content())
+        .lines()
+        .any(|line| normalize_whitespace(line).contains(needle))
+}
+
+/// Path of a file in `parent`, following a rename made by `commit`
+fn path_in_parent(
+    repo: &Repository,
+    commit: &Commit,
+    parent: &Commit,
+    path: &Path,
+) -> Option<PathBuf> {
+    if blob_at(parent, path).is_some() {
+        return Some(path.to_path_buf());
+    }
+
+    let old_tree = parent.tree().ok()?;
+    let new_tree = commit.tree().ok()?;
+    let mut diff = repo
+        .diff_tree_to_tree(Some(&old_tree), Some(&new_tree), None)
+        .ok()?;
+    diff.find_similar(None).ok()?;
+
+    diff.deltas()
+        .filter(|delta| matches!(delta.status(), Delta::Renamed | Delta::Copied))
+        .find(|delta| delta.new_file().path() == Some(path))
+        .and_then(|delta| delta.old_file().path().map(Path::to_path_buf))
+}
+
+/// Find the commit that first introduced `needle` into a file
+///
+/// Walks back from `start` while a parent still contains the marker text,
+/// following renames. Commits that leave the file untouched are skipped
+/// without reading its contents. Commits in
+/// `ignore_revs` are never reported; the marker is attributed to the last
+/// commit before them that changed the file instead.
+fn find_origin(
+    repo: &Repository,
+    relative_path: &Path,
+    needle: &str,
+    start: Oid,
+    ignore_revs: &HashSet<Oid>,
+) -> Option<Oid> {
+    let mut commit = repo.find_commit(start).ok()?;
+    let mut path = relative_path.to_path_buf();
+    let mut blob = blob_at(&commit, &path)?;
+    if !blob_contains(repo, blob, needle) {
+        return None;
+    }
+
+    for _ in 0..MAX_ORIGIN_DEPTH {
+        let mut next = None;
+        for parent in commit.parents() {
+            let Some(parent_path) = path_in_parent(repo, &commit, &parent, &path) else {
+                continue;
+            };
+            let Some(parent_blob) = blob_at(&parent, &parent_path) else {
+                continue;
             };
+            // An unchanged blob still contains the text
+            if parent_blob == blob || blob_contains(repo, parent_blob, needle) {
+                next = Some((parent, parent_path, parent_blob));
+                break;
+            }
+        }
+
+        match next {
+            Some((parent, parent_path, parent_blob)) => {
+                commit = parent;
+                path = parent_path;
+                blob = parent_blob;
+            }
+            None => break,
+        }
+    }
+
+    // Attribute past ignored commits to the previous change to the file
+    while ignore_revs.contains(&commit.id()) {
+        let Some(mut candidate) = commit.parents().next() else {
+            break;
+        };
+        let Some(candidate_path) = path_in_parent(repo, &commit, &candidate, &path) else {
+            break;
+        };
+        path = candidate_path;
+        let candidate_blob = blob_at(&candidate, &path);
+
+        while let Some(parent) = candidate.parents().next() {
+            if blob_at(&parent, &path) != candidate_blob {
+                break;
+            }
+            candidate = parent;
+        }
+        commit = candidate;
+    }
+
+    Some(commit.id())
+}
+
+/// Fill in the `introduced_*` fields of blame results for a file
+///
+/// The marker's age is measured from its introduction rather than from the
+/// last change to the line.
+fn attribute_origins(
+    repo: &Repository,
+    workdir: &Path,
+    file_path: &Path,
+    results: &mut [(usize, GitBlameInfo)],
+    needles: &[String],
+    ignore_revs: &HashSet<Oid>,
+) {
+    let Some(relative_path) = relative_to_workdir(workdir, file_path) else {
+        return;
+    };
+
+    let Some(head) = repo.head().ok().and_then(|head| head.target()) else {
+        return;
+    };
+
+    for (marker_idx, info) in results.iter_mut() {
+        let Some(origin) = find_origin(
+            repo,
+            &relative_path,
+            &needles[*marker_idx],
+            head,
+            ignore_revs,
+        ) else {
+            continue;
+        };
+        let Ok(introduced) = blame_info_for_commit(repo, origin) else {
+            continue;
+        };
+
+        info.age_days = introduced.age_days;
+        info.introduced_by = Some(introduced.author);
+        info.introduced_at = Some(introduced.commit_time);
+        info.introduced_commit = Some(introduced.commit_hash);
+    }
+}
+
+/// Blame settings and state shared by the workers blaming a repository
+///
+/// `git2::Repository` is not `Sync`, so each worker opens its own handle with
+/// [`Blamer::open`]. Results are cached under `.fossil/cache` unless caching
+/// is disabled. In origin mode each marker is also traced back to the commit
+/// that introduced it.
+pub struct Blamer<'a> {
+    workdir: PathBuf,
+    repo_path: PathBuf,
+    config: &'a BlameConfig,
+    ignore_revs: HashSet<Oid>,
+    cache: Option<BlameCache>,
+}
+
+impl<'a> Blamer<'a> {
+    /// Prepare to blame files in `repo`
+    pub fn new(repo: &Repository, config: &'a BlameConfig) -> Result<Self> {
+        let workdir = repo
+            .workdir()
+            .context("Repository has no working directory")?
+            .to_path_buf();
+
+        // Caching is best-effort: an unwritable cache directory just disables it
+        let cache = if config.cache {
+            BlameCache::open(&workdir).ok()
+        } else {
+            None
+        };
+        // Entries are keyed by HEAD, so those of earlier commits are dead weight
+        if let (Some(cache), Some(head)) = (&cache, head_commit(repo)) {
+            let _ = cache.prune(&head);
+        }
+
+        Ok(Self {
+            repo_path: repo.path().to_path_buf(),
+            ignore_revs: load_ignore_revs(repo, &config.ignore_revs),
+            workdir,
+            config,
+            cache,
+        })
+    }
+
+    /// Open a handle to the repository for a worker
+    pub fn open(&self) -> Option<Repository> {
+        Repository::open(&self.repo_path).ok()
+    }
+
+    /// Thread pool with the configured number of workers
+    pub fn pool(&self) -> Result<rayon::ThreadPool> {
+        // 0 workers lets rayon pick one thread per CPU
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(self.config.workers)
+            .build()
+            .context("Failed to build blame thread pool")
+    }
 
-            // Cache and assign
-            blame_cache.insert(line_number, git_info.clone());
-            marker.git_info = Some(git_info);
+    /// Blame lines of a file, as for [`blame_file_lines`]
+    ///
+    /// `needles` holds each marker's [`marker_needle`] by marker index and is
+    /// only used in origin mode.
+    fn blame(
+        &self,
+        repo: &Repository,
+        file_path: &Path,
+        lines: &[(usize, usize)],
+        needles: &[String],
+    ) -> Vec<(usize, GitBlameInfo)> {
+        let mut results = blame_file_lines(
+            repo,
+            &self.workdir,
+            file_path,
+            lines,
+            self.config,
+            &self.ignore_revs,
+            self.cache.as_ref(),
+        );
+        if self.config.origin {
+            attribute_origins(
+                repo,
+                &self.workdir,
+                file_path,
+                &mut results,
+                needles,
+                &self.ignore_revs,
+            );
         }
+        results
+    }
+
+    /// Enrich markers that are all from one file, running blame once
+    pub fn enrich_file(&self, repo: &Repository, markers: &mut [DebtMarker]) {
+        let Some(file_path) = markers.first().map(|m| m.file_path.clone()) else {
+            return;
+        };
+        let lines: Vec<(usize, usize)> = markers
+            .iter()
+            .enumerate()
+            .map(|(idx, marker)| (idx, marker.line_number))
+            .collect();
+        let needles: Vec<String> = if self.config.origin {
+            markers.iter().map(marker_needle).collect()
+        } else {
+            Vec::new()
+        };
+
+        for (marker_idx, git_info) in self.blame(repo, &file_path, &lines, &needles) {
+            markers[marker_idx].git_info = Some(git_info);
+        }
+    }
+}
+
+/// Batch enrich markers with git blame information
+///
+/// Groups markers by file and runs git blame once per file, blaming files in
+/// parallel on a rayon pool (see [`Blamer`]).
+pub fn enrich_markers_batch(
+    markers: &mut [DebtMarker],
+    repo: Option<&Repository>,
+    config: &BlameConfig,
+) -> Result<()> {
+    let repo = match repo {
+        Some(r) => r,
+        None => return Ok(()), // No repository, skip enrichment
+    };
+    let blamer = Blamer::new(repo, config)?;
+
+    // Group markers by file path
+    let mut markers_by_file: HashMap<PathBuf, Vec<(usize, usize)>> = HashMap::new();
+    for (idx, marker) in markers.iter().enumerate() {
+        markers_by_file
+            .entry(marker.file_path.clone())
+            .or_default()
+            .push((idx, marker.line_number));
+    }
+    let files: Vec<(PathBuf, Vec<(usize, usize)>)> = markers_by_file.into_iter().collect();
+
+    let needles: Vec<String> = if config.origin {
+        markers.iter().map(marker_needle).collect()
+    } else {
+        Vec::new()
+    };
+
+    let results: Vec<(usize, GitBlameInfo)> = blamer.pool()?.install(|| {
+        files
+            .par_iter()
+            .map_init(
+                || blamer.open(),
+                |worker_repo, (file_path, lines)| match worker_repo {
+                    Some(worker_repo) => blamer.blame(worker_repo, file_path, lines, &needles),
+                    None => Vec::new(),
+                },
+            )
+            .flatten()
+            .collect()
+    });
+
+    for (marker_idx, git_info) in results {
+        markers[marker_idx].git_info = Some(git_info);
     }
 
     Ok(())
@@ -295,7 +1030,7 @@ mod tests {
         let repo = Repository::open(temp_dir.path()).unwrap();
         let file_path = temp_dir.path().join("test.rs");
 
-        let info = blame_line(&repo, &file_path, 2).unwrap();
+        let info = blame_line(&repo, &file_path, 2, &BlameConfig::default()).unwrap();
         assert!(info.is_some());
 
         let git_info = info.unwrap();
@@ -311,14 +1046,325 @@ mod tests {
         let repo = Repository::open(temp_dir.path()).unwrap();
         let file_path = temp_dir.path().join("test.rs");
 
-        let info = enrich_with_git_info(Some(&repo), &file_path, 2);
+        let info = enrich_with_git_info(Some(&repo), &file_path, 2, &BlameConfig::default());
         assert!(info.is_some());
 
         let git_info = info.unwrap();
         assert_eq!(git_info.author, "Test User");
 
         // Test with None repository
-        let no_info = enrich_with_git_info(None, &file_path, 2);
+        let no_info = enrich_with_git_info(None, &file_path, 2, &BlameConfig::default());
         assert!(no_info.is_none());
     }
+
+    #[test]
+    fn test_enrich_markers_batch_parallel() {
+        let temp_dir = create_test_repo();
+        let repo = Repository::open(temp_dir.path()).unwrap();
+
+        // A second committed file so blame runs on more than one worker
+        let other_file = temp_dir.path().join("other.rs");
+        fs::write(&other_file, "// FIXME: other\n").unwrap();
+        Command::new("git")
+            .args(["add", "."])
+            .current_dir(temp_dir.path())
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args(["commit", "-m", "Add other file"])
+            .current_dir(temp_dir.path())
+            .output()
+            .unwrap();
+
+        let mut markers = vec![
+            DebtMarker::test(temp_dir.path().join("test.rs"), 2, "TODO"),
+            DebtMarker::test(other_file, 1, "TODO"),
+            DebtMarker::test(temp_dir.path().join("test.rs"), 99, "TODO"), // Not in blame
+        ];
+
+        let config = BlameConfig {
+            workers: 2,
+            track_copies: false,
+            cache: false,
+            origin: false,
+            ignore_revs: vec![],
+        };
+        enrich_markers_batch(&mut markers, Some(&repo), &config).unwrap();
+
+        assert_eq!(markers[0].git_info.as_ref().unwrap().author, "Test User");
+        assert_eq!(markers[1].git_info.as_ref().unwrap().author, "Test User");
+        assert!(markers[2].git_info.is_none());
+
+        // Without a repository nothing is enriched
+        let mut markers = vec![DebtMarker::test(temp_dir.path().join("test.rs"), 2, "TODO")];
+        enrich_markers_batch(&mut markers, None, &BlameConfig::default()).unwrap();
+        assert!(markers[0].git_info.is_none());
+    }
+
+    #[test]
+    fn test_enrich_markers_batch_uses_cache() {
+        let temp_dir = create_test_repo();
+        let repo = Repository::open(temp_dir.path()).unwrap();
+        let config = BlameConfig::default();
+
+        let mut markers = vec![DebtMarker::test(temp_dir.path().join("test.rs"), 2, "TODO")];
+        enrich_markers_batch(&mut markers, Some(&repo), &config).unwrap();
+        let first = markers[0].git_info.clone().unwrap();
+
+        let head = head_commit(&repo).unwrap();
+        let head_dir = temp_dir
+            .path()
+            .join(cache::CACHE_DIR)
+            .join("blame")
+            .join(&head);
+        let entries: Vec<_> = fs::read_dir(&head_dir).unwrap().flatten().collect();
+        assert_eq!(entries.len(), 1);
+
+        // Second run is served from the cache
+        let mut markers = vec![DebtMarker::test(temp_dir.path().join("test.rs"), 2, "TODO")];
+        enrich_markers_batch(&mut markers, Some(&repo), &config).unwrap();
+        let second = markers[0].git_info.clone().unwrap();
+        assert_eq!(first.commit_hash, second.commit_hash);
+        assert_eq!(first.author, second.author);
+
+        // A corrupt entry is recovered from by re-blaming
+        fs::write(entries[0].path(), b"garbage").unwrap();
+        let mut markers = vec![DebtMarker::test(temp_dir.path().join("test.rs"), 2, "TODO")];
+        enrich_markers_batch(&mut markers, Some(&repo), &config).unwrap();
+        assert_eq!(markers[0].git_info.as_ref().unwrap().author, "Test User");
+
+        // Scanning a new commit prunes the old HEAD's entries
+        fs::write(temp_dir.path().join("other.rs"), "// other\n").unwrap();
+        commit_as(temp_dir.path(), "Test User", "Add other.rs");
+        let mut markers = vec![DebtMarker::test(temp_dir.path().join("test.rs"), 2, "TODO")];
+        enrich_markers_batch(&mut markers, Some(&repo), &config).unwrap();
+        assert!(!head_dir.exists());
+        assert!(
+            head_dir
+                .with_file_name(head_commit(&repo).unwrap())
+                .exists()
+        );
+    }
+
+    #[test]
+    fn test_cache_keeps_identical_files_apart() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        fs::copy(repo_path.join("test.rs"), repo_path.join("copy.rs")).unwrap();
+        commit_as(repo_path, "Copy Author", "Copy test.rs");
+
+        let repo = Repository::open(repo_path).unwrap();
+        let config = BlameConfig {
+            track_copies: false,
+            ..Default::default()
+        };
+        let mut markers = vec![DebtMarker::test(repo_path.join("test.rs"), 2, "TODO")];
+        enrich_markers_batch(&mut markers, Some(&repo), &config).unwrap();
+        let mut markers = vec![DebtMarker::test(repo_path.join("copy.rs"), 2, "TODO")];
+        enrich_markers_batch(&mut markers, Some(&repo), &config).unwrap();
+
+        // Same blob, but copy.rs has its own history
+        assert_eq!(markers[0].git_info.as_ref().unwrap().author, "Copy Author");
+    }
+
+    fn commit_as(repo_path: &Path, author: &str, message: &str) {
+        Command::new("git")
+            .args(["add", "-A"])
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args([
+                "-c",
+                &format!("user.name={}", author),
+                "commit",
+                "-m",
+                message,
+            ])
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+    }
+
+    fn origin_config() -> BlameConfig {
+        BlameConfig {
+            cache: false,
+            origin: true,
+            ..BlameConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_origin_survives_reformat_and_rename() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+
+        fs::write(repo_path.join("lib.rs"), "fn a() {\n// TODO: old debt\n}\n").unwrap();
+        commit_as(repo_path, "Original Author", "Add lib");
+
+        fs::write(
+            repo_path.join("lib.rs"),
+            "fn a() {\n    // TODO:   old debt\n}\n",
+        )
+        .unwrap();
+        commit_as(repo_path, "Formatter", "Reformat");
+
+        Command::new("git")
+            .args(["mv", "lib.rs", "renamed.rs"])
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+        commit_as(repo_path, "Renamer", "Rename lib");
+
+        let repo = Repository::open(repo_path).unwrap();
+        let mut marker = DebtMarker::test(repo_path.join("renamed.rs"), 2, "TODO");
+        marker.line_content = "    // TODO:   old debt".to_string();
+        let mut markers = vec![marker];
+
+        enrich_markers_batch(&mut markers, Some(&repo), &origin_config()).unwrap();
+        let info = markers[0].git_info.as_ref().unwrap();
+        assert_eq!(info.author, "Formatter");
+        assert_eq!(info.introduced_by.as_deref(), Some("Original Author"));
+        assert!(info.introduced_at.is_some());
+        assert_eq!(info.introduced_commit.as_ref().unwrap().len(), 7);
+
+        // Origin mode is off by default
+        let mut markers = vec![DebtMarker::test(repo_path.join("renamed.rs"), 2, "TODO")];
+        let config = BlameConfig {
+            cache: false,
+            ..BlameConfig::default()
+        };
+        enrich_markers_batch(&mut markers, Some(&repo), &config).unwrap();
+        assert!(
+            markers[0]
+                .git_info
+                .as_ref()
+                .unwrap()
+                .introduced_by
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_origin_honors_ignore_revs() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+
+        fs::write(repo_path.join("lib.rs"), "// TODO fix this\n").unwrap();
+        commit_as(repo_path, "Alice", "Add lib");
+
+        fs::write(repo_path.join("lib.rs"), "// TODO: fix this\n").unwrap();
+        commit_as(repo_path, "Lint Bot", "Normalize markers");
+
+        let repo = Repository::open(repo_path).unwrap();
+        let bot_commit = repo.head().unwrap().peel_to_commit().unwrap().id();
+
+        let mut marker = DebtMarker::test(repo_path.join("lib.rs"), 1, "TODO");
+        marker.line_content = "// TODO: fix this".to_string();
+
+        let mut markers = vec![marker.clone()];
+        enrich_markers_batch(&mut markers, Some(&repo), &origin_config()).unwrap();
+        let info = markers[0].git_info.as_ref().unwrap();
+        assert_eq!(info.introduced_by.as_deref(), Some("Lint Bot"));
+
+        fs::write(
+            repo_path.join(IGNORE_REVS_FILE),
+            format!("# Marker normalization\n{}\n", bot_commit),
+        )
+        .unwrap();
+        assert!(load_ignore_revs(&repo, &[]).contains(&bot_commit));
+
+        let mut markers = vec![marker];
+        enrich_markers_batch(&mut markers, Some(&repo), &origin_config()).unwrap();
+        let info = markers[0].git_info.as_ref().unwrap();
+        assert_eq!(info.introduced_by.as_deref(), Some("Alice"));
+    }
+
+    #[test]
+    fn test_map_to_parent() {
+        // Line 2 modified, lines 4-5 added, old line 6 deleted after new line 7
+        let changes = vec![(2, 1, 2, 1), (3, 0, 4, 2), (6, 1, 7, 0)];
+        assert_eq!(map_to_parent(&changes, 1), Some(1));
+        assert_eq!(map_to_parent(&changes, 2), Some(2));
+        assert_eq!(map_to_parent(&changes, 3), Some(3));
+        assert_eq!(map_to_parent(&changes, 4), None);
+        assert_eq!(map_to_parent(&changes, 6), Some(4));
+        assert_eq!(map_to_parent(&changes, 7), Some(5));
+        assert_eq!(map_to_parent(&changes, 8), Some(7));
+    }
+
+    #[test]
+    fn test_ignore_revs_attribution() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        let file_path = repo_path.join("lib.rs");
+
+        fs::write(&file_path, "fn a() {\n// TODO: alice debt\n}\n").unwrap();
+        commit_as(repo_path, "Alice", "Add lib");
+
+        fs::write(
+            &file_path,
+            "// License: MIT\nfn a() {\n    // TODO: alice debt\n}\n",
+        )
+        .unwrap();
+        commit_as(repo_path, "Format Bot", "Format and add headers");
+
+        let repo = Repository::open(repo_path).unwrap();
+        let bot_commit = repo.head().unwrap().peel_to_commit().unwrap().id();
+
+        let config = BlameConfig::default();
+        let info = blame_line(&repo, &file_path, 3, &config).unwrap().unwrap();
+        assert_eq!(info.author, "Format Bot");
+
+        // Revisions listed in the config
+        let config = BlameConfig {
+            cache: false,
+            ignore_revs: vec![bot_commit.to_string()],
+            ..BlameConfig::default()
+        };
+        let info = blame_line(&repo, &file_path, 3, &config).unwrap().unwrap();
+        assert_eq!(info.author, "Alice");
+
+        // Lines added by an ignored commit stay attributed to it
+        let info = blame_line(&repo, &file_path, 1, &config).unwrap().unwrap();
+        assert_eq!(info.author, "Format Bot");
+
+        let mut markers = vec![DebtMarker::test(file_path.clone(), 3, "TODO")];
+        enrich_markers_batch(&mut markers, Some(&repo), &config).unwrap();
+        assert_eq!(markers[0].git_info.as_ref().unwrap().author, "Alice");
+
+        // Revisions listed in .git-blame-ignore-revs, with the cache enabled
+        fs::write(
+            repo_path.join(IGNORE_REVS_FILE),
+            format!("# Formatting\n{}\n", bot_commit),
+        )
+        .unwrap();
+        let config = BlameConfig::default();
+        let info = blame_line(&repo, &file_path, 3, &config).unwrap().unwrap();
+        assert_eq!(info.author, "Alice");
+
+        for _ in 0..2 {
+            let mut markers = vec![DebtMarker::test(file_path.clone(), 3, "TODO")];
+            enrich_markers_batch(&mut markers, Some(&repo), &config).unwrap();
+            assert_eq!(markers[0].git_info.as_ref().unwrap().author, "Alice");
+        }
+    }
+
+    #[test]
+    fn test_file_activity() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        fs::write(repo_path.join("test.rs"), "// changed\n").unwrap();
+        fs::write(repo_path.join("other.rs"), "// new\n").unwrap();
+        commit_as(repo_path, "Alice", "Change files");
+
+        let repo = Repository::open(repo_path).unwrap();
+        let activity = file_activity(&repo, Duration::from_secs(24 * 60 * 60)).unwrap();
+        assert_eq!(activity.len(), 2);
+
+        let test = &activity[Path::new("test.rs")];
+        assert_eq!(test.commits, 2);
+        assert!(test.authors.contains("Alice") && test.authors.contains("Test User"));
+        assert_eq!(activity[Path::new("other.rs")].commits, 1);
+    }
 }
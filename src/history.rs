@@ -0,0 +1,611 @@
+use crate::models::{Config, DebtReport};
+use crate::{scanner, scoring};
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use comfy_table::{Cell, Color, ContentArrangement, Table, presets::UTF8_FULL};
+use git2::{Oid, Repository};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Directory (relative to the repository root) holding debt snapshots
+pub const HISTORY_DIR: &str = ".fossil/history";
+
+/// Bump when the snapshot format changes
+const HISTORY_VERSION: u32 = 1;
+
+/// Characters used to draw sparklines, lowest to highest
+const SPARK_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// A debt report recorded at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Format version of the snapshot file
+    pub version: u32,
+
+    /// When the snapshot was taken (the commit time for backfilled snapshots)
+    pub taken_at: DateTime<Utc>,
+
+    /// HEAD commit the snapshot describes, if the scan was in a git repository
+    pub commit: Option<String>,
+
+    /// The report at that point
+    pub report: DebtReport,
+}
+
+impl Snapshot {
+    /// Create a snapshot of a report
+    pub fn new(report: DebtReport, taken_at: DateTime<Utc>, commit: Option<String>) -> Self {
+        Self {
+            version: HISTORY_VERSION,
+            taken_at,
+            commit,
+            report,
+        }
+    }
+
+    /// Snapshot identifier: the date plus the abbreviated commit
+    pub fn id(&self) -> String {
+        let commit = self.commit.as_deref().unwrap_or("nohead");
+        format!("{}-{:.7}", self.taken_at.format("%Y-%m-%d"), commit)
+    }
+}
+
+/// Snapshots stored as JSON files under `.fossil/history`
+#[derive(Debug, Clone)]
+pub struct HistoryStore {
+    dir: PathBuf,
+}
+
+impl HistoryStore {
+    /// Start tracking history for a repository
+    ///
+    /// Returns the store and whether it was newly created.
+    pub fn init(repo_root: &Path) -> Result<(Self, bool)> {
+        let dir = repo_root.join(HISTORY_DIR);
+        let created = !dir.exists();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create history directory: {}", dir.display()))?;
+        Ok((Self { dir }, created))
+    }
+
+    /// Open the history store of a repository that has been initialized
+    pub fn open(repo_root: &Path) -> Result<Self> {
+        let dir = repo_root.join(HISTORY_DIR);
+        if !dir.is_dir() {
+            bail!(
+                "History tracking is not initialized in {} (run `fossil history init`)",
+                repo_root.display()
+            );
+        }
+        Ok(Self { dir })
+    }
+
+    /// Directory holding the snapshots
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Store a snapshot, replacing any with the same date and commit
+    pub fn save(&self, snapshot: &Snapshot) -> Result<PathBuf> {
+        let path = self.dir.join(format!("{}.json", snapshot.id()));
+        let contents =
+            serde_json::to_string_pretty(snapshot).context("Failed to serialize snapshot")?;
+        fs::write(&path, contents)
+            .with_context(|| format!("Failed to write snapshot: {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Load all snapshots, oldest first
+    pub fn load_all(&self) -> Result<Vec<Snapshot>> {
+        let entries = fs::read_dir(&self.dir)
+            .with_context(|| format!("Failed to read history directory: {}", self.dir.display()))?;
+
+        let mut snapshots = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            snapshots.push(load_snapshot(&path)?);
+        }
+
+        snapshots.sort_by_key(|s| s.taken_at);
+        Ok(snapshots)
+    }
+
+    /// Find a snapshot by id, date (`YYYY-MM-DD`, latest that day), commit
+    /// prefix, or `latest`
+    pub fn find(&self, key: &str) -> Result<Snapshot> {
+        let snapshots = self.load_all()?;
+        select_snapshot(snapshots, key).with_context(|| format!("No snapshot matches '{}'", key))
+    }
+}
+
+fn load_snapshot(path: &Path) -> Result<Snapshot> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read snapshot: {}", path.display()))?;
+    let snapshot: Snapshot = serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse snapshot: {}", path.display()))?;
+
+    if snapshot.version > HISTORY_VERSION {
+        bail!(
+            "Snapshot {} was written by a newer version of fossil (format {})",
+            path.display(),
+            snapshot.version
+        );
+    }
+
+    Ok(snapshot)
+}
+
+/// Pick first-parent commits on HEAD to snapshot when backfilling, newest first
+///
+/// Sample points run back from HEAD's commit time in steps of `every` until
+/// `since` before now. Each point takes the newest commit at or before it;
+/// points with no commit since the previous sample are skipped.
+pub fn backfill_commits(repo: &Repository, since: Duration, every: Duration) -> Result<Vec<Oid>> {
+    let step = every.as_secs() as i64;
+    if step == 0 {
+        bail!("Backfill interval must be at least one day");
+    }
+    let cutoff = Utc::now().timestamp() - since.as_secs() as i64;
+
+    let mut revwalk = repo.revwalk().context("Failed to walk history")?;
+    revwalk
+        .push_head()
+        .context("Repository has no HEAD commit")?;
+    revwalk.simplify_first_parent()?;
+
+    let mut point: Option<i64> = None;
+    let mut commits = Vec::new();
+    for oid in revwalk {
+        let oid = oid?;
+        let time = repo.find_commit(oid)?.time().seconds();
+
+        let current = *point.get_or_insert(time);
+        if current < cutoff {
+            break;
+        }
+        if time <= current {
+            commits.push(oid);
+            // Move to the first sample point before this commit
+            point = Some(current - ((current - time) / step + 1) * step);
+        }
+    }
+
+    Ok(commits)
+}
+
+/// Scan a commit's tree straight from the object database into a snapshot
+///
+/// Only `prefix` (a directory relative to the repository root; empty for the
+/// whole tree) is scanned, and marker paths start with `scan_path`, as they
+/// would when scanning a checkout. Backfilled snapshots carry no blame data,
+/// so markers are scored by severity alone: without ages, and without churn,
+/// since `churn_window` counts back from today rather than from the commit.
+pub fn snapshot_commit(
+    repo: &Repository,
+    commit_id: Oid,
+    prefix: &Path,
+    scan_path: &Path,
+    config: &Config,
+) -> Result<Snapshot> {
+    let commit = repo.find_commit(commit_id)?;
+    let mut tree = commit.tree()?;
+    if !prefix.as_os_str().is_empty() {
+        tree = tree
+            .get_path(prefix)
+            .and_then(|entry| entry.to_object(repo))
+            .and_then(|object| object.peel_to_tree())
+            .with_context(|| {
+                format!(
+                    "{} is not a directory in commit {:.7}",
+                    prefix.display(),
+                    commit_id
+                )
+            })?;
+    }
+
+    let mut markers = scanner::scan_tree(repo, &tree, prefix, scan_path, config)?;
+    scoring::score_markers(&mut markers, &config.scoring, None, scan_path, prefix)?;
+    let taken_at = DateTime::from_timestamp(commit.time().seconds(), 0).unwrap_or_else(Utc::now);
+    let report = DebtReport::new(markers, scan_path.to_path_buf());
+
+    Ok(Snapshot::new(report, taken_at, Some(commit_id.to_string())))
+}
+
+/// Pick the snapshot a key refers to from snapshots sorted oldest first
+fn select_snapshot(snapshots: Vec<Snapshot>, key: &str) -> Option<Snapshot> {
+    if key == "latest" {
+        return snapshots.into_iter().last();
+    }
+
+    snapshots.into_iter().rev().find(|s| {
+        s.id() == key
+            || s.taken_at.format("%Y-%m-%d").to_string() == key
+            || s.commit.as_deref().is_some_and(|c| c.starts_with(key))
+    })
+}
+
+/// Draw values as a sparkline scaled between their minimum and maximum
+pub fn sparkline(values: &[usize]) -> String {
+    let (Some(&min), Some(&max)) = (values.iter().min(), values.iter().max()) else {
+        return String::new();
+    };
+    let range = max - min;
+
+    values
+        .iter()
+        .map(|&value| {
+            // Equal values are drawn at mid height
+            let level = ((value - min) * (SPARK_CHARS.len() - 1)).checked_div(range);
+            SPARK_CHARS[level.unwrap_or(SPARK_CHARS.len() / 2)]
+        })
+        .collect()
+}
+
+/// Marker types across snapshots, most common (in total) first
+fn marker_types<'a>(reports: impl Iterator<Item = &'a DebtReport>) -> Vec<String> {
+    let mut totals: HashMap<&str, usize> = HashMap::new();
+    for report in reports {
+        for (marker_type, count) in &report.by_type {
+            *totals.entry(marker_type).or_insert(0) += count;
+        }
+    }
+
+    let mut types: Vec<_> = totals.into_iter().collect();
+    types.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    types.into_iter().map(|(t, _)| t.to_string()).collect()
+}
+
+fn format_delta(from: usize, to: usize) -> String {
+    match to.cmp(&from) {
+        std::cmp::Ordering::Greater => format!("+{}", to - from),
+        std::cmp::Ordering::Less => format!("-{}", from - to),
+        std::cmp::Ordering::Equal => "0".to_string(),
+    }
+}
+
+/// Format the trend across the most recent `limit` snapshots
+pub fn format_trend(snapshots: &[Snapshot], limit: usize) -> String {
+    if snapshots.is_empty() {
+        return "No snapshots recorded yet (run `fossil history snapshot`)".to_string();
+    }
+
+    let recent = &snapshots[snapshots.len().saturating_sub(limit)..];
+    let types = marker_types(recent.iter().map(|s| &s.report));
+    let mut output = String::new();
+
+    output.push_str(&format!("Debt history ({} snapshots):\n", recent.len()));
+    let mut table = Table::new();
+    let mut header = vec![
+        Cell::new("Date").fg(Color::Cyan),
+        Cell::new("Commit").fg(Color::Cyan),
+        Cell::new("Total").fg(Color::Cyan),
+    ];
+    header.extend(types.iter().map(|t| Cell::new(t).fg(Color::Cyan)));
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(header);
+
+    for snapshot in recent {
+        let mut row = vec![
+            snapshot.taken_at.format("%Y-%m-%d").to_string(),
+            format!("{:.7}", snapshot.commit.as_deref().unwrap_or("-")),
+            snapshot.report.total_count.to_string(),
+        ];
+        row.extend(types.iter().map(|t| {
+            snapshot
+                .report
+                .by_type
+                .get(t)
+                .copied()
+                .unwrap_or(0)
+                .to_string()
+        }));
+        table.add_row(row);
+    }
+    output.push_str(&format!("{}\n\n", table));
+
+    // One sparkline per series, labels padded to line up
+    let mut series = vec![(
+        "Total".to_string(),
+        recent
+            .iter()
+            .map(|s| s.report.total_count)
+            .collect::<Vec<_>>(),
+    )];
+    for marker_type in &types {
+        series.push((
+            marker_type.clone(),
+            recent
+                .iter()
+                .map(|s| s.report.by_type.get(marker_type).copied().unwrap_or(0))
+                .collect(),
+        ));
+    }
+    let width = series.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+
+    output.push_str("Trend:\n");
+    for (name, values) in &series {
+        let first = values.first().copied().unwrap_or(0);
+        let last = values.last().copied().unwrap_or(0);
+        output.push_str(&format!(
+            "  {:<width$}  {}  {} → {} ({})\n",
+            name,
+            sparkline(values),
+            first,
+            last,
+            format_delta(first, last),
+            width = width
+        ));
+    }
+
+    output
+}
+
+/// Format the change in debt between two snapshots
+pub fn format_comparison(from: &Snapshot, to: &Snapshot) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("Comparing {} → {}\n", from.id(), to.id()));
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Type").fg(Color::Cyan),
+            Cell::new(from.id()).fg(Color::Cyan),
+            Cell::new(to.id()).fg(Color::Cyan),
+            Cell::new("Change").fg(Color::Cyan),
+        ]);
+
+    for marker_type in marker_types([&from.report, &to.report].into_iter()) {
+        let before = from.report.by_type.get(&marker_type).copied().unwrap_or(0);
+        let after = to.report.by_type.get(&marker_type).copied().unwrap_or(0);
+        table.add_row(vec![
+            marker_type,
+            before.to_string(),
+            after.to_string(),
+            format_delta(before, after),
+        ]);
+    }
+    table.add_row(vec![
+        "Total".to_string(),
+        from.report.total_count.to_string(),
+        to.report.total_count.to_string(),
+        format_delta(from.report.total_count, to.report.total_count),
+    ]);
+    output.push_str(&format!("{}\n", table));
+
+    // Files whose marker count changed the most
+    let mut files: Vec<(&PathBuf, usize, usize)> = from
+        .report
+        .by_file
+        .keys()
+        .chain(to.report.by_file.keys())
+        .map(|file| {
+            (
+                file,
+                from.report.by_file.get(file).copied().unwrap_or(0),
+                to.report.by_file.get(file).copied().unwrap_or(0),
+            )
+        })
+        .filter(|(_, before, after)| before != after)
+        .collect();
+    files.sort_by(|a, b| b.1.abs_diff(b.2).cmp(&a.1.abs_diff(a.2)).then(a.0.cmp(b.0)));
+    files.dedup_by(|a, b| a.0 == b.0);
+
+    if !files.is_empty() {
+        output.push_str("\nLargest changes by file:\n");
+        for (file, before, after) in files.into_iter().take(10) {
+            output.push_str(&format!(
+                "  {:>5}  {}\n",
+                format_delta(before, after),
+                file.display()
+            ));
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DebtMarker;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn test_report(types: &[(&str, &str)]) -> DebtReport {
+        let markers = types
+            .iter()
+            .enumerate()
+            .map(|(idx, (marker_type, file))| DebtMarker::test(file, idx + 1, marker_type))
+            .collect();
+        DebtReport::new(markers, PathBuf::from("."))
+    }
+
+    fn test_snapshot(date: &str, commit: &str, types: &[(&str, &str)]) -> Snapshot {
+        let taken_at = DateTime::parse_from_rfc3339(&format!("{}T12:00:00Z", date))
+            .unwrap()
+            .with_timezone(&Utc);
+        Snapshot::new(test_report(types), taken_at, Some(commit.to_string()))
+    }
+
+    #[test]
+    fn test_store_roundtrip_and_find() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(HistoryStore::open(temp_dir.path()).is_err());
+
+        let (store, created) = HistoryStore::init(temp_dir.path()).unwrap();
+        assert!(created);
+        assert!(!HistoryStore::init(temp_dir.path()).unwrap().1);
+
+        let newer = test_snapshot("2024-02-01", "bbbbbbbbbb", &[("TODO", "a.rs")]);
+        let older = test_snapshot(
+            "2024-01-01",
+            "aaaaaaaaaa",
+            &[("TODO", "a.rs"), ("FIXME", "b.rs")],
+        );
+        let path = store.save(&newer).unwrap();
+        assert!(path.ends_with("2024-02-01-bbbbbbb.json"));
+        store.save(&older).unwrap();
+
+        // Saving the same date and commit replaces the snapshot
+        store.save(&older).unwrap();
+
+        let snapshots = store.load_all().unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].id(), "2024-01-01-aaaaaaa");
+
+        assert_eq!(store.find("latest").unwrap().id(), newer.id());
+        assert_eq!(store.find("2024-01-01").unwrap().report.total_count, 2);
+        assert_eq!(store.find("bbbb").unwrap().id(), newer.id());
+        assert!(store.find("2023-12-31").is_err());
+    }
+
+    #[test]
+    fn test_newer_format_is_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let (store, _) = HistoryStore::init(temp_dir.path()).unwrap();
+
+        let mut snapshot = test_snapshot("2024-01-01", "aaaaaaa", &[]);
+        snapshot.version = HISTORY_VERSION + 1;
+        store.save(&snapshot).unwrap();
+
+        let err = store.load_all().unwrap_err();
+        assert!(err.to_string().contains("newer version"));
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[]), "");
+        assert_eq!(sparkline(&[0, 7, 14]), "▁▄█");
+        assert_eq!(sparkline(&[3, 3]), "▅▅");
+    }
+
+    #[test]
+    fn test_format_trend_and_comparison() {
+        let snapshots = vec![
+            test_snapshot(
+                "2024-01-01",
+                "aaaaaaa",
+                &[("TODO", "a.rs"), ("TODO", "a.rs")],
+            ),
+            test_snapshot(
+                "2024-01-08",
+                "bbbbbbb",
+                &[("TODO", "a.rs"), ("FIXME", "b.rs")],
+            ),
+        ];
+
+        let trend = format_trend(&snapshots, 10);
+        assert!(trend.contains("2024-01-08"));
+        assert!(trend.contains("2 → 1 (-1)")); // TODO series
+        assert!(trend.contains("0 → 1 (+1)")); // FIXME series
+
+        let comparison = format_comparison(&snapshots[0], &snapshots[1]);
+        assert!(comparison.contains("FIXME"));
+        assert!(comparison.contains("+1  b.rs"));
+        assert!(comparison.contains("-1  a.rs"));
+
+        assert!(format_trend(&[], 10).contains("No snapshots"));
+    }
+
+    #[test]
+    fn test_backfill() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let now = Utc::now().timestamp();
+        let git = |args: &[&str], days_ago: i64| {
+            let date = format!("@{} +0000", now - days_ago * 86_400);
+            Command::new("git")
+                .args(args)
+                .env("GIT_AUTHOR_DATE", &date)
+                .env("GIT_COMMITTER_DATE", &date)
+                .current_dir(root)
+                .output()
+                .unwrap();
+        };
+        let commit = |days_ago: i64, content: &str| {
+            fs::write(root.join("lib.rs"), content).unwrap();
+            git(&["add", "-A"], days_ago);
+            git(
+                &[
+                    "-c",
+                    "user.name=Test",
+                    "-c",
+                    "user.email=test@example.com",
+                    "commit",
+                    "-m",
+                    "Change",
+                ],
+                days_ago,
+            );
+        };
+
+        git(&["init"], 0);
+        commit(40, "// TODO: one\n");
+        commit(20, "// TODO: one\n// FIXME: two\n");
+        commit(9, "// TODO: one\n// FIXME: two\n// HACK: three\n");
+        commit(8, "// TODO: one\n");
+        commit(2, "// TODO: one\n// TODO: four\n");
+
+        let repo = Repository::open(root).unwrap();
+        let week = Duration::from_secs(7 * 86_400);
+
+        // Points at 2, 9, 16 and 23 days ago; the commit from 8 days ago falls
+        // between points and is never sampled
+        let commits = backfill_commits(&repo, Duration::from_secs(30 * 86_400), week).unwrap();
+        let snapshots: Vec<Snapshot> = commits
+            .iter()
+            .map(|&oid| {
+                snapshot_commit(
+                    &repo,
+                    oid,
+                    Path::new(""),
+                    Path::new("."),
+                    &Config::default(),
+                )
+                .unwrap()
+            })
+            .collect();
+        let totals: Vec<usize> = snapshots.iter().map(|s| s.report.total_count).collect();
+        assert_eq!(totals, vec![2, 3, 2, 1]);
+        assert_eq!(snapshots[1].report.by_type.get("HACK"), Some(&1));
+        // Scored by severity weight: TODO 2, FIXME and HACK 4
+        assert_eq!(snapshots[0].report.debt_index, 4.0);
+        assert_eq!(snapshots[1].report.debt_index, 10.0);
+        assert_eq!(
+            snapshots[1].report.score_by_file.get(Path::new("./lib.rs")),
+            Some(&10.0)
+        );
+        assert_eq!(
+            snapshots[0].commit.as_deref(),
+            Some(repo.head().unwrap().target().unwrap().to_string().as_str())
+        );
+        assert!(snapshots[0].taken_at > snapshots[1].taken_at);
+
+        // A shorter window stops at 9 days ago
+        let commits = backfill_commits(&repo, Duration::from_secs(10 * 86_400), week).unwrap();
+        assert_eq!(commits.len(), 2);
+
+        assert!(backfill_commits(&repo, week, Duration::from_secs(0)).is_err());
+        assert!(
+            snapshot_commit(
+                &repo,
+                commits[0],
+                Path::new("missing"),
+                Path::new("missing"),
+                &Config::default()
+            )
+            .is_err()
+        );
+    }
+}
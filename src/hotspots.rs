@@ -0,0 +1,123 @@
+use crate::fingerprint;
+use crate::git::FileActivity;
+use crate::models::DebtReport;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A file with both debt and recent churn
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Hotspot {
+    /// File path as reported by the scan
+    pub file_path: PathBuf,
+
+    /// Number of debt markers in the file
+    pub markers: usize,
+
+    /// Sum of the file's marker scores
+    pub score: f64,
+
+    /// Commits that changed the file in the window
+    pub commits: usize,
+
+    /// Distinct authors of those commits
+    pub authors: usize,
+
+    /// Ranking value: debt score times commits
+    pub hotspot: f64,
+}
+
+/// Rank files where debt and churn overlap, most urgent first
+///
+/// `activity` is keyed by paths relative to the repository root; `prefix` is
+/// the location of the report's scan path within it. Files without markers or
+/// without recent commits are left out: debt nobody touches can wait.
+pub fn rank_hotspots(
+    report: &DebtReport,
+    activity: &HashMap<PathBuf, FileActivity>,
+    prefix: &Path,
+) -> Vec<Hotspot> {
+    let mut hotspots: Vec<Hotspot> = report
+        .by_file
+        .iter()
+        .filter_map(|(file_path, &markers)| {
+            let repo_path = fingerprint::repo_relative(file_path, &report.scan_path, prefix);
+            let file_activity = activity.get(&repo_path)?;
+            if file_activity.commits == 0 {
+                return None;
+            }
+
+            let score = report.score_by_file.get(file_path).copied().unwrap_or(0.0);
+            Some(Hotspot {
+                file_path: file_path.clone(),
+                markers,
+                score,
+                commits: file_activity.commits,
+                authors: file_activity.authors.len(),
+                hotspot: ((score * file_activity.commits as f64) * 100.0).round() / 100.0,
+            })
+        })
+        .collect();
+
+    hotspots.sort_by(|a, b| {
+        b.hotspot
+            .total_cmp(&a.hotspot)
+            .then(b.commits.cmp(&a.commits))
+            .then(a.file_path.cmp(&b.file_path))
+    });
+    hotspots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DebtMarker;
+    use std::collections::HashSet;
+
+    fn marker(file: &str, score: f64) -> DebtMarker {
+        DebtMarker {
+            score,
+            ..DebtMarker::test(file, 1, "TODO")
+        }
+    }
+
+    fn activity(commits: usize, authors: &[&str]) -> FileActivity {
+        FileActivity {
+            commits,
+            authors: authors
+                .iter()
+                .map(|a| a.to_string())
+                .collect::<HashSet<_>>(),
+        }
+    }
+
+    #[test]
+    fn test_rank_hotspots() {
+        let report = DebtReport::new(
+            vec![
+                marker("./a.rs", 2.0),
+                marker("./a.rs", 2.0),
+                marker("./b.rs", 10.0),
+                marker("./stale.rs", 50.0),
+            ],
+            PathBuf::from("."),
+        );
+        // Scanned from the `sub` directory of the repository
+        let activity = HashMap::from([
+            (PathBuf::from("sub/a.rs"), activity(12, &["alice", "bob"])),
+            (PathBuf::from("sub/b.rs"), activity(2, &["alice"])),
+            (PathBuf::from("sub/untracked.rs"), activity(30, &["carol"])),
+        ]);
+
+        let hotspots = rank_hotspots(&report, &activity, Path::new("sub"));
+        let files: Vec<_> = hotspots.iter().map(|h| h.file_path.clone()).collect();
+        assert_eq!(
+            files,
+            vec![PathBuf::from("./a.rs"), PathBuf::from("./b.rs")]
+        );
+        assert_eq!(hotspots[0].markers, 2);
+        assert_eq!(hotspots[0].authors, 2);
+        assert_eq!(hotspots[0].hotspot, 48.0);
+        assert_eq!(hotspots[1].hotspot, 20.0);
+    }
+}
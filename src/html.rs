@@ -0,0 +1,156 @@
+use crate::models::DebtReport;
+use anyhow::{Context, Result};
+
+/// Stylesheet inlined into the report
+const STYLE: &str = include_str!("html/report.css");
+
+/// Script that renders the tables, charts and directory tree from the report
+/// data embedded in the page
+const SCRIPT: &str = include_str!("html/report.js");
+
+/// Escape text for HTML content and attribute values
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Serialize the report for a `<script type="application/json">` element
+///
+/// `<` is written as a JSON escape so marker text such as `</script>` can't
+/// end the element early.
+fn report_data(report: &DebtReport) -> Result<String> {
+    let json = serde_json::to_string(report).context("Failed to serialize report for HTML")?;
+    Ok(json.replace('<', "\\u003c"))
+}
+
+/// Format report as a self-contained HTML page
+///
+/// Styles, script and data are all inlined, so the file works offline (e.g.
+/// as a CI artifact). The summary is rendered here; the sortable marker table,
+/// charts and directory tree are built by the script.
+pub fn format_html(report: &DebtReport) -> Result<String> {
+    let scan_path = escape(&report.scan_path.display().to_string());
+    let generated = report.scan_time.format("%Y-%m-%d %H:%M:%S UTC");
+    let files = report.by_file.len();
+    let authors = report.by_author.len();
+
+    Ok(format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>Fossil - Technical Debt Report: {scan_path}</title>
+<style>
+{STYLE}</style>
+</head>
+<body>
+<header>
+<h1>Fossil - Technical Debt Report</h1>
+<div class="meta">Scanned <code>{scan_path}</code> · Generated {generated}</div>
+<div class="cards">
+<div class="card"><div class="value">{total}</div><div class="label">Markers</div></div>
+<div class="card"><div class="value">{debt_index:.1}</div><div class="label">Debt index</div></div>
+<div class="card"><div class="value">{files}</div><div class="label">Files</div></div>
+<div class="card"><div class="value">{authors}</div><div class="label">Authors</div></div>
+</div>
+</header>
+<noscript><p>Enable JavaScript to view the charts and marker tables.</p></noscript>
+<main>
+<h2>Overview</h2>
+<div class="charts">
+<section class="chart"><h3>By type</h3><div id="chart-type"></div></section>
+<section class="chart"><h3>By author</h3><div id="chart-author"></div></section>
+<section class="chart"><h3>By age</h3><div id="chart-age"></div></section>
+</div>
+<h2>Directories</h2>
+<div class="tree" id="tree"></div>
+<h2>Markers</h2>
+<div class="filters">
+<input type="search" id="search" placeholder="Search type, file, description, assignee, author" aria-label="Search markers">
+<select id="filter-type" aria-label="Type"><option value="">All types</option></select>
+<select id="filter-severity" aria-label="Severity"><option value="">All severities</option></select>
+<select id="filter-author" aria-label="Author"><option value="">All authors</option></select>
+<select id="filter-age" aria-label="Age"><option value="">All ages</option></select>
+<span id="path-filter" hidden>In <code></code> <button type="button" id="clear-path">clear</button></span>
+<span class="meta" id="marker-count"></span>
+</div>
+<table id="markers">
+<thead>
+<tr>
+<th data-key="severityRank" data-numeric="true">Severity</th>
+<th data-key="type">Type</th>
+<th data-key="location">Location</th>
+<th data-key="description">Description</th>
+<th data-key="assignee">Assignee</th>
+<th data-key="author">Author</th>
+<th data-key="age" data-numeric="true">Age</th>
+<th data-key="score" data-numeric="true">Score</th>
+</tr>
+</thead>
+<tbody id="markers-body"></tbody>
+</table>
+</main>
+<script type="application/json" id="fossil-data">{data}</script>
+<script>
+{SCRIPT}</script>
+</body>
+</html>
+"#,
+        total = report.total_count,
+        debt_index = report.debt_index,
+        data = report_data(report)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DebtMarker;
+    use std::path::PathBuf;
+
+    fn marker(line_content: &str) -> DebtMarker {
+        DebtMarker {
+            line_content: line_content.to_string(),
+            description: "close </script> tags".to_string(),
+            context_before: vec!["fn main() {".to_string()],
+            context_after: vec!["}".to_string()],
+            score: 2.0,
+            ..DebtMarker::test("src/<b>.rs", 3, "TODO")
+        }
+    }
+
+    #[test]
+    fn test_format_html() {
+        let report = DebtReport::new(
+            vec![marker("// TODO: close </script> tags")],
+            PathBuf::from("<project>"),
+        );
+        let html = format_html(&report).unwrap();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Fossil - Technical Debt Report: &lt;project&gt;</title>"));
+        assert!(html.contains("<div class=\"value\">2.0</div><div class=\"label\">Debt index"));
+
+        // Marker text can't break out of the data element, and the data
+        // round-trips
+        assert_eq!(html.matches("</script>").count(), 2);
+        let start = html.find("id=\"fossil-data\">").unwrap() + "id=\"fossil-data\">".len();
+        let end = start + html[start..].find("</script>").unwrap();
+        let data: serde_json::Value = serde_json::from_str(&html[start..end]).unwrap();
+        assert_eq!(
+            data["markers"][0]["line_content"],
+            "// TODO: close </script> tags"
+        );
+        assert_eq!(data["markers"][0]["context_before"][0], "fn main() {");
+
+        // Everything is inlined
+        assert!(html.contains(STYLE) && html.contains(SCRIPT));
+        for external in ["src=", "href=", "http://", "https://", "@import"] {
+            assert!(!html.contains(external), "external reference: {}", external);
+        }
+    }
+}
@@ -0,0 +1,399 @@
+(function () {
+  "use strict";
+
+  const report = JSON.parse(document.getElementById("fossil-data").textContent);
+
+  const SEVERITIES = ["critical", "high", "medium", "low", "info"];
+  const AGE_BUCKETS = [
+    { label: "< 1 month", maxDays: 30 },
+    { label: "1-3 months", maxDays: 90 },
+    { label: "3-6 months", maxDays: 180 },
+    { label: "6-12 months", maxDays: 365 },
+    { label: "1-2 years", maxDays: 730 },
+    { label: "> 2 years", maxDays: Infinity },
+  ];
+  const UNKNOWN_AGE = "Unknown";
+  const AUTHOR_CHART_LIMIT = 15;
+
+  function el(tag, className, text) {
+    const node = document.createElement(tag);
+    if (className) node.className = className;
+    if (text !== undefined) node.textContent = text;
+    return node;
+  }
+
+  function normalizePath(path) {
+    return path.replace(/\\/g, "/").replace(/^\.\//, "");
+  }
+
+  function ageBucket(marker) {
+    if (!marker.git_info) return UNKNOWN_AGE;
+    const days = marker.git_info.age_days;
+    return AGE_BUCKETS.find((bucket) => days < bucket.maxDays).label;
+  }
+
+  const rows = report.markers.map((marker) => ({
+    marker: marker,
+    type: marker.marker_type,
+    severity: marker.severity,
+    severityRank: SEVERITIES.length - SEVERITIES.indexOf(marker.severity),
+    file: normalizePath(marker.file_path),
+    line: marker.line_number,
+    description: marker.description,
+    assignee: marker.assignee || "",
+    author: marker.git_info ? marker.git_info.author : "",
+    age: marker.git_info ? marker.git_info.age_days : null,
+    score: marker.score || 0,
+    bucket: ageBucket(marker),
+  }));
+
+  const state = {
+    search: "",
+    type: "",
+    severity: "",
+    author: "",
+    bucket: "",
+    path: "",
+    sortKey: "age",
+    sortDescending: true,
+    expanded: new Set(),
+  };
+
+  // Filters
+
+  function matches(row) {
+    if (state.type && row.type !== state.type) return false;
+    if (state.severity && row.severity !== state.severity) return false;
+    if (state.author && row.author !== state.author) return false;
+    if (state.bucket && row.bucket !== state.bucket) return false;
+    if (state.path && row.file !== state.path && !row.file.startsWith(state.path + "/")) {
+      return false;
+    }
+    if (state.search) {
+      const haystack = [row.type, row.file, row.description, row.assignee, row.author]
+        .join(" ")
+        .toLowerCase();
+      if (!haystack.includes(state.search)) return false;
+    }
+    return true;
+  }
+
+  function fillSelect(id, values, key) {
+    const select = document.getElementById(id);
+    values.forEach((value) => select.appendChild(el("option", "", value)));
+    select.addEventListener("change", () => {
+      state[key] = select.value;
+      render();
+    });
+  }
+
+  function setFilter(key, value) {
+    state[key] = value;
+    const ids = { type: "filter-type", author: "filter-author", bucket: "filter-age" };
+    if (ids[key]) document.getElementById(ids[key]).value = value;
+    render();
+    document.getElementById("markers").scrollIntoView({ behavior: "smooth" });
+  }
+
+  function setPathFilter(path) {
+    state.path = path;
+    const box = document.getElementById("path-filter");
+    box.hidden = !path;
+    box.querySelector("code").textContent = path;
+    render();
+    document.getElementById("markers").scrollIntoView({ behavior: "smooth" });
+  }
+
+  // Sorting
+
+  function compareRows(a, b) {
+    const key = state.sortKey;
+    let result;
+    if (key === "location") {
+      result = a.file.localeCompare(b.file) || a.line - b.line;
+    } else {
+      const x = a[key];
+      const y = b[key];
+      // Unknown values sort last in either direction
+      if (x === null || y === null) {
+        return (x === null) - (y === null);
+      }
+      result = typeof x === "number" ? x - y : x.localeCompare(y);
+    }
+    return state.sortDescending ? -result : result;
+  }
+
+  function setupSorting() {
+    document.querySelectorAll("#markers th[data-key]").forEach((th) => {
+      th.addEventListener("click", () => {
+        const key = th.dataset.key;
+        if (state.sortKey === key) {
+          state.sortDescending = !state.sortDescending;
+        } else {
+          state.sortKey = key;
+          state.sortDescending = th.dataset.numeric === "true";
+        }
+        render();
+      });
+    });
+  }
+
+  // Marker table
+
+  function highlightMarker(parent, text, markerType) {
+    const index = text.indexOf(markerType);
+    if (index < 0) {
+      parent.appendChild(document.createTextNode(text));
+      return;
+    }
+    parent.appendChild(document.createTextNode(text.slice(0, index)));
+    parent.appendChild(el("mark", "", markerType));
+    parent.appendChild(document.createTextNode(text.slice(index + markerType.length)));
+  }
+
+  function contextBlock(marker) {
+    const pre = el("pre", "context");
+    const before = marker.context_before || [];
+    const continuation = marker.continuation_lines || [];
+    const lines = before.concat([marker.line_content], continuation, marker.context_after || []);
+    const first = marker.line_number - before.length;
+
+    lines.forEach((text, i) => {
+      const isMarker = i >= before.length && i <= before.length + continuation.length;
+      const line = el("span", isMarker ? "line marker-line" : "line");
+      line.appendChild(el("span", "ln", String(first + i)));
+      if (i === before.length) {
+        highlightMarker(line, text, marker.marker_type);
+      } else {
+        line.appendChild(document.createTextNode(text));
+      }
+      pre.appendChild(line);
+    });
+    return pre;
+  }
+
+  function contextMeta(marker) {
+    const parts = [];
+    if (marker.git_info) {
+      const info = marker.git_info;
+      parts.push(info.author + " <" + info.author_email + ">");
+      parts.push("commit " + info.commit_hash);
+      if (info.introduced_commit) parts.push("introduced in " + info.introduced_commit);
+    }
+    if (marker.issue_refs && marker.issue_refs.length) {
+      parts.push("issues: " + marker.issue_refs.join(", "));
+    }
+    return el("div", "context-meta", parts.join(" · "));
+  }
+
+  function markerRow(row, index) {
+    const tr = el("tr", "marker");
+    tr.appendChild(el("td", "severity severity-" + row.severity, row.severity));
+    tr.appendChild(el("td", "", row.type));
+    const location = el("td");
+    location.appendChild(el("code", "", row.file + ":" + row.line));
+    tr.appendChild(location);
+    tr.appendChild(el("td", "", row.description));
+    tr.appendChild(el("td", "", row.assignee));
+    tr.appendChild(el("td", "", row.author));
+    tr.appendChild(el("td", "num", row.age === null ? "?" : row.age + " days"));
+    tr.appendChild(el("td", "num", row.score.toFixed(1)));
+    tr.addEventListener("click", () => {
+      if (state.expanded.has(index)) {
+        state.expanded.delete(index);
+      } else {
+        state.expanded.add(index);
+      }
+      render();
+    });
+    return tr;
+  }
+
+  function detailsRow(row) {
+    const tr = el("tr", "details");
+    const td = el("td");
+    td.colSpan = 8;
+    td.appendChild(contextBlock(row.marker));
+    td.appendChild(contextMeta(row.marker));
+    tr.appendChild(td);
+    return tr;
+  }
+
+  function render() {
+    const visible = rows.filter(matches).sort(compareRows);
+    const body = document.getElementById("markers-body");
+    body.replaceChildren();
+
+    visible.forEach((row) => {
+      const index = rows.indexOf(row);
+      body.appendChild(markerRow(row, index));
+      if (state.expanded.has(index)) body.appendChild(detailsRow(row));
+    });
+    if (!visible.length) {
+      const tr = el("tr");
+      const td = el("td", "empty", "No markers match the filters");
+      td.colSpan = 8;
+      tr.appendChild(td);
+      body.appendChild(tr);
+    }
+
+    document.getElementById("marker-count").textContent =
+      "Showing " + visible.length + " of " + rows.length + " markers";
+    document.querySelectorAll("#markers th[data-key]").forEach((th) => {
+      if (th.dataset.key === state.sortKey) {
+        th.setAttribute("aria-sort", state.sortDescending ? "descending" : "ascending");
+      } else {
+        th.removeAttribute("aria-sort");
+      }
+    });
+  }
+
+  // Charts
+
+  function countBy(key) {
+    const counts = new Map();
+    rows.forEach((row) => counts.set(row[key], (counts.get(row[key]) || 0) + 1));
+    return counts;
+  }
+
+  function barChart(id, entries, onSelect) {
+    const chart = document.getElementById(id);
+    const max = Math.max(1, ...entries.map((entry) => entry[1]));
+    if (!entries.length) {
+      chart.appendChild(el("div", "empty", "No data"));
+    }
+    entries.forEach(([label, count]) => {
+      const row = el("button", "bar-row");
+      row.type = "button";
+      row.title = label + ": " + count;
+      row.appendChild(el("span", "bar-label", label));
+      const track = el("span", "bar-track");
+      const bar = el("span", "bar");
+      bar.style.display = "block";
+      bar.style.width = (100 * count) / max + "%";
+      track.appendChild(bar);
+      row.appendChild(track);
+      row.appendChild(el("span", "bar-count", String(count)));
+      row.addEventListener("click", () => onSelect(label));
+      chart.appendChild(row);
+    });
+  }
+
+  function renderCharts() {
+    const byType = [...countBy("type")].sort((a, b) => b[1] - a[1] || a[0].localeCompare(b[0]));
+    barChart("chart-type", byType, (type) => setFilter("type", type));
+
+    const byAuthor = [...countBy("author")]
+      .filter(([author]) => author)
+      .sort((a, b) => b[1] - a[1] || a[0].localeCompare(b[0]))
+      .slice(0, AUTHOR_CHART_LIMIT);
+    barChart("chart-author", byAuthor, (author) => setFilter("author", author));
+
+    const buckets = countBy("bucket");
+    const byAge = AGE_BUCKETS.map((bucket) => bucket.label)
+      .concat([UNKNOWN_AGE])
+      .filter((label) => buckets.has(label))
+      .map((label) => [label, buckets.get(label)]);
+    barChart("chart-age", byAge, (bucket) => setFilter("bucket", bucket));
+  }
+
+  // Directory tree
+
+  function buildTree() {
+    const root = { children: new Map(), count: 0 };
+    rows.forEach((row) => {
+      let node = root;
+      node.count += 1;
+      let path = "";
+      row.file.split("/").forEach((part) => {
+        path = path ? path + "/" + part : part;
+        if (!node.children.has(part)) {
+          node.children.set(part, { name: part, path: path, children: new Map(), count: 0 });
+        }
+        node = node.children.get(part);
+        node.count += 1;
+      });
+    });
+    return root;
+  }
+
+  function treeLabel(node) {
+    const label = el("span");
+    const button = el("button", "", node.name);
+    button.type = "button";
+    button.title = "Show markers in " + node.path;
+    button.addEventListener("click", (event) => {
+      event.preventDefault();
+      setPathFilter(node.path);
+    });
+    label.appendChild(button);
+    label.appendChild(el("span", "count", String(node.count)));
+    return label;
+  }
+
+  function treeList(node, depth) {
+    const list = el("ul");
+    const children = [...node.children.values()].sort(
+      (a, b) => (b.children.size > 0) - (a.children.size > 0) || a.name.localeCompare(b.name)
+    );
+    children.forEach((child) => {
+      const item = el("li");
+      if (child.children.size) {
+        const details = el("details");
+        details.open = depth < 1;
+        const summary = el("summary");
+        summary.appendChild(treeLabel(child));
+        details.appendChild(summary);
+        details.appendChild(treeList(child, depth + 1));
+        item.appendChild(details);
+      } else {
+        item.appendChild(treeLabel(child));
+      }
+      list.appendChild(item);
+    });
+    return list;
+  }
+
+  function renderTree() {
+    const tree = document.getElementById("tree");
+    const root = buildTree();
+    if (root.count) {
+      tree.appendChild(treeList(root, 0));
+    } else {
+      tree.appendChild(el("div", "empty", "No markers found"));
+    }
+  }
+
+  // Setup
+
+  function uniqueSorted(key) {
+    return [...new Set(rows.map((row) => row[key]).filter((value) => value))].sort((a, b) =>
+      a.localeCompare(b)
+    );
+  }
+
+  document.getElementById("search").addEventListener("input", (event) => {
+    state.search = event.target.value.trim().toLowerCase();
+    render();
+  });
+  fillSelect("filter-type", uniqueSorted("type"), "type");
+  fillSelect(
+    "filter-severity",
+    SEVERITIES.filter((severity) => rows.some((row) => row.severity === severity)),
+    "severity"
+  );
+  fillSelect("filter-author", uniqueSorted("author"), "author");
+  fillSelect(
+    "filter-age",
+    AGE_BUCKETS.map((bucket) => bucket.label)
+      .concat([UNKNOWN_AGE])
+      .filter((label) => rows.some((row) => row.bucket === label)),
+    "bucket"
+  );
+  document.getElementById("clear-path").addEventListener("click", () => setPathFilter(""));
+
+  setupSorting();
+  renderCharts();
+  renderTree();
+  render();
+})();
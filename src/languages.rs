@@ -0,0 +1,841 @@
+use std::ops::Range;
+use std::path::Path;
+
+/// A block comment delimiter pair
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockComment {
+    pub open: &'static str,
+    pub close: &'static str,
+    /// Whether block comments nest (Rust, Haskell)
+    pub nested: bool,
+}
+
+/// Comment syntax for a family of languages
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CommentSyntax {
+    /// Human-readable name of the language family
+    pub name: &'static str,
+
+    /// Line comment leaders, e.g. `//` or `#`
+    pub line: &'static [&'static str],
+
+    /// Block comment delimiters, e.g. `/* */`
+    pub block: &'static [BlockComment],
+
+    /// String delimiters; comment leaders inside strings are ignored
+    pub strings: &'static [&'static str],
+
+    /// `'` starts a char literal, lifetime or prime rather than a string
+    pub char_literals: bool,
+
+    /// Rust-style raw strings (`r"..."`, `r#"..."#`, `br"..."`)
+    pub raw_strings: bool,
+
+    /// Strings may span several lines
+    pub multiline_strings: bool,
+
+    /// Block comments are only comments as docstrings, the first statement of
+    /// a module, class or function; elsewhere they are multi-line strings
+    pub docstrings: bool,
+
+    /// Comments may follow code on the same line (`foo(); // TODO`)
+    pub trailing: bool,
+}
+
+const C_BLOCK: BlockComment = BlockComment {
+    open: "/*",
+    close: "*/",
+    nested: false,
+};
+
+const HTML_BLOCK: BlockComment = BlockComment {
+    open: "<!--",
+    close: "-->",
+    nested: false,
+};
+
+const RUST: CommentSyntax = CommentSyntax {
+    name: "Rust",
+    line: &["//"],
+    block: &[BlockComment {
+        open: "/*",
+        close: "*/",
+        nested: true,
+    }],
+    strings: &["\""],
+    char_literals: true,
+    raw_strings: true,
+    multiline_strings: true,
+    docstrings: false,
+    trailing: true,
+};
+
+const C_LIKE: CommentSyntax = CommentSyntax {
+    name: "C-like",
+    line: &["//"],
+    block: &[C_BLOCK],
+    strings: &["\"", "'"],
+    char_literals: false,
+    raw_strings: false,
+    multiline_strings: false,
+    docstrings: false,
+    trailing: true,
+};
+
+const JAVASCRIPT: CommentSyntax = CommentSyntax {
+    name: "JavaScript",
+    line: &["//"],
+    block: &[C_BLOCK],
+    strings: &["\"", "'", "`"],
+    char_literals: false,
+    raw_strings: false,
+    multiline_strings: false,
+    docstrings: false,
+    trailing: true,
+};
+
+const GO: CommentSyntax = CommentSyntax {
+    name: "Go",
+    line: &["//"],
+    block: &[C_BLOCK],
+    strings: &["\"", "`"],
+    char_literals: true,
+    raw_strings: false,
+    multiline_strings: false,
+    docstrings: false,
+    trailing: true,
+};
+
+const CSS: CommentSyntax = CommentSyntax {
+    name: "CSS",
+    line: &[],
+    block: &[C_BLOCK],
+    strings: &["\"", "'"],
+    char_literals: false,
+    raw_strings: false,
+    multiline_strings: false,
+    docstrings: false,
+    trailing: true,
+};
+
+const PHP: CommentSyntax = CommentSyntax {
+    name: "PHP",
+    line: &["//", "#"],
+    block: &[C_BLOCK],
+    strings: &["\"", "'"],
+    char_literals: false,
+    raw_strings: false,
+    multiline_strings: false,
+    docstrings: false,
+    trailing: true,
+};
+
+const PYTHON: CommentSyntax = CommentSyntax {
+    name: "Python",
+    line: &["#"],
+    // Docstrings are strings, but they hold notes the same way comments do;
+    // other triple-quoted strings are just strings
+    block: &[
+        BlockComment {
+            open: "\"\"\"",
+            close: "\"\"\"",
+            nested: false,
+        },
+        BlockComment {
+            open: "'''",
+            close: "'''",
+            nested: false,
+        },
+    ],
+    strings: &["\"", "'"],
+    char_literals: false,
+    raw_strings: false,
+    multiline_strings: false,
+    docstrings: true,
+    trailing: true,
+};
+
+const HASH: CommentSyntax = CommentSyntax {
+    name: "Shell-like",
+    line: &["#"],
+    block: &[],
+    strings: &["\"", "'"],
+    char_literals: false,
+    raw_strings: false,
+    multiline_strings: false,
+    docstrings: false,
+    trailing: true,
+};
+
+const HASH_AND_C: CommentSyntax = CommentSyntax {
+    name: "HCL-like",
+    line: &["#", "//"],
+    block: &[C_BLOCK],
+    strings: &["\""],
+    char_literals: false,
+    raw_strings: false,
+    multiline_strings: false,
+    docstrings: false,
+    trailing: true,
+};
+
+const SQL: CommentSyntax = CommentSyntax {
+    name: "SQL",
+    line: &["--"],
+    block: &[C_BLOCK],
+    strings: &["'", "\""],
+    char_literals: false,
+    raw_strings: false,
+    multiline_strings: false,
+    docstrings: false,
+    trailing: true,
+};
+
+const LUA: CommentSyntax = CommentSyntax {
+    name: "Lua",
+    line: &["--"],
+    block: &[BlockComment {
+        open: "--[[",
+        close: "]]",
+        nested: false,
+    }],
+    strings: &["\"", "'"],
+    char_literals: false,
+    raw_strings: false,
+    multiline_strings: false,
+    docstrings: false,
+    trailing: true,
+};
+
+const HASKELL: CommentSyntax = CommentSyntax {
+    name: "Haskell",
+    line: &["--"],
+    block: &[BlockComment {
+        open: "{-",
+        close: "-}",
+        nested: true,
+    }],
+    strings: &["\""],
+    char_literals: true,
+    raw_strings: false,
+    multiline_strings: false,
+    docstrings: false,
+    trailing: true,
+};
+
+const LISP: CommentSyntax = CommentSyntax {
+    name: "Lisp",
+    line: &[";"],
+    block: &[BlockComment {
+        open: "#|",
+        close: "|#",
+        nested: true,
+    }],
+    strings: &["\""],
+    char_literals: false,
+    raw_strings: false,
+    multiline_strings: true,
+    docstrings: false,
+    trailing: true,
+};
+
+const INI: CommentSyntax = CommentSyntax {
+    name: "INI",
+    line: &[";", "#"],
+    block: &[],
+    strings: &[],
+    char_literals: false,
+    raw_strings: false,
+    multiline_strings: false,
+    docstrings: false,
+    trailing: false,
+};
+
+const ERLANG: CommentSyntax = CommentSyntax {
+    name: "Erlang",
+    line: &["%"],
+    block: &[],
+    strings: &["\""],
+    char_literals: false,
+    raw_strings: false,
+    multiline_strings: false,
+    docstrings: false,
+    trailing: true,
+};
+
+const LATEX: CommentSyntax = CommentSyntax {
+    name: "LaTeX",
+    line: &["%"],
+    block: &[],
+    strings: &[],
+    char_literals: false,
+    raw_strings: false,
+    multiline_strings: false,
+    docstrings: false,
+    trailing: true,
+};
+
+const MARKUP: CommentSyntax = CommentSyntax {
+    name: "Markup",
+    line: &[],
+    block: &[HTML_BLOCK],
+    strings: &[],
+    char_literals: false,
+    raw_strings: false,
+    multiline_strings: false,
+    docstrings: false,
+    trailing: true,
+};
+
+/// Fallback for unknown file types: common comment leaders, only at the start
+/// of a line, so prose and string contents are not mistaken for comments
+pub const GENERIC: CommentSyntax = CommentSyntax {
+    name: "Generic",
+    line: &["//", "#"],
+    block: &[C_BLOCK, HTML_BLOCK],
+    strings: &[],
+    char_literals: false,
+    raw_strings: false,
+    multiline_strings: false,
+    docstrings: false,
+    trailing: false,
+};
+
+/// Look up the comment syntax for a file based on its extension or name
+pub fn syntax_for_path(path: &Path) -> &'static CommentSyntax {
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    match file_name.as_str() {
+        "makefile" | "gnumakefile" | "dockerfile" | "containerfile" | "cmakelists.txt"
+        | "gemfile" | "rakefile" | "vagrantfile" | "justfile" | ".gitignore" | ".bashrc"
+        | ".zshrc" | ".profile" | ".fossilrc" => return &HASH,
+        _ => {}
+    }
+
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    match ext.as_str() {
+        "rs" => &RUST,
+        "c" | "h" | "cc" | "cpp" | "cxx" | "hh" | "hpp" | "hxx" | "cs" | "java" | "kt" | "kts"
+        | "scala" | "swift" | "dart" | "groovy" | "gradle" | "proto" | "zig" | "sol" | "scss"
+        | "less" | "m" | "mm" => &C_LIKE,
+        "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" | "vue" | "svelte" => {
+            &JAVASCRIPT
+        }
+        "go" => &GO,
+        "css" => &CSS,
+        "php" => &PHP,
+        "py" | "pyi" | "pyx" => &PYTHON,
+        "sh" | "bash" | "zsh" | "fish" | "rb" | "pl" | "pm" | "r" | "yaml" | "yml" | "toml"
+        | "cmake" | "mk" | "ex" | "exs" | "nim" | "cr" | "ps1" | "jl" | "tcl" | "conf"
+        | "dockerfile" => &HASH,
+        "tf" | "tfvars" | "hcl" | "nix" => &HASH_AND_C,
+        "sql" => &SQL,
+        "lua" => &LUA,
+        "hs" | "lhs" | "elm" | "purs" => &HASKELL,
+        "lisp" | "lsp" | "cl" | "el" | "clj" | "cljs" | "cljc" | "edn" | "scm" | "ss" | "rkt"
+        | "asm" | "s" => &LISP,
+        "ini" | "cfg" => &INI,
+        "erl" | "hrl" => &ERLANG,
+        "tex" | "sty" | "cls" | "bib" => &LATEX,
+        "html" | "htm" | "xhtml" | "xml" | "svg" | "md" | "markdown" => &MARKUP,
+        _ => &GENERIC,
+    }
+}
+
+/// How a comment span is delimited
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CommentStyle {
+    /// Line comment with the given leader
+    Line(&'static str),
+    /// Block comment with the given closing delimiter
+    Block(&'static str),
+}
+
+/// The text of one comment found on a line
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommentSpan<'a> {
+    /// Comment text without its delimiters
+    pub text: &'a str,
+
+    /// How the comment is delimited
+    pub style: CommentStyle,
+
+    /// Block comment that was opened on an earlier line
+    pub continued: bool,
+
+    /// Block comment that is still open at the end of the line
+    pub open_at_end: bool,
+
+    /// Code precedes the comment on the same line
+    pub trailing: bool,
+
+    /// Byte offset where the comment (including its delimiter) starts;
+    /// 0 for a block comment continued from an earlier line
+    pub start: usize,
+}
+
+/// Comments and string literals found on one line
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LexedLine<'a> {
+    /// Comments on the line, in order
+    pub comments: Vec<CommentSpan<'a>>,
+
+    /// Byte ranges covered by string literals (including delimiters)
+    pub strings: Vec<Range<usize>>,
+}
+
+impl LexedLine<'_> {
+    /// Whether the byte offset falls inside a string literal
+    pub fn in_string(&self, offset: usize) -> bool {
+        self.strings.iter().any(|range| range.contains(&offset))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum LexState {
+    Code,
+    Block {
+        index: usize,
+        depth: usize,
+    },
+    Str {
+        close: String,
+        escapes: bool,
+        multiline: bool,
+    },
+}
+
+/// Line-by-line lexer that separates comments from code and strings
+///
+/// State (open block comments, multi-line strings) is carried between calls
+/// to [`CommentLexer::lex`], so lines must be fed in order.
+pub struct CommentLexer {
+    syntax: &'static CommentSyntax,
+    state: LexState,
+    /// A docstring may start here: at the top of the file or after a
+    /// `def`/`class` header
+    docstring_allowed: bool,
+    /// Unclosed brackets of a `def`/`class` header spanning several lines
+    header_depth: Option<usize>,
+}
+
+impl CommentLexer {
+    pub fn new(syntax: &'static CommentSyntax) -> Self {
+        Self {
+            syntax,
+            state: LexState::Code,
+            docstring_allowed: true,
+            header_depth: None,
+        }
+    }
+
+    /// Return the comments and strings found on the next line of the file
+    pub fn lex<'a>(&mut self, line: &'a str) -> LexedLine<'a> {
+        let syntax = self.syntax;
+        let bytes = line.as_bytes();
+        let mut spans = Vec::new();
+        let mut strings = Vec::new();
+        let mut i = 0;
+
+        // Start of the current string literal (0 if continued from an earlier line)
+        let mut string_start = 0;
+
+        // Start of the current block comment's text, and whether it began on
+        // an earlier line
+        let mut block_start = 0;
+        let mut block_open = 0;
+        let mut block_continued = matches!(self.state, LexState::Block { .. });
+        let mut block_trailing = false;
+
+        // Code outside comments and strings, and whether a docstring opened
+        let mut code = String::new();
+        let mut docstring = false;
+
+        while i < bytes.len() {
+            let rest = &line[i..];
+
+            match &mut self.state {
+                LexState::Block { index, depth } => {
+                    let block = &syntax.block[*index];
+                    if block.nested && block.open != block.close && rest.starts_with(block.open) {
+                        *depth += 1;
+                        i += block.open.len();
+                    } else if rest.starts_with(block.close) {
+                        *depth -= 1;
+                        if *depth == 0 {
+                            spans.push(CommentSpan {
+                                text: &line[block_start..i],
+                                style: CommentStyle::Block(block.close),
+                                continued: block_continued,
+                                open_at_end: false,
+                                trailing: block_trailing,
+                                start: block_open,
+                            });
+                            self.state = LexState::Code;
+                        }
+                        i += block.close.len();
+                    } else {
+                        i += next_char_len(rest);
+                    }
+                }
+                LexState::Str { close, escapes, .. } => {
+                    if *escapes && bytes[i] == b'\\' {
+                        i += 1 + next_char_len(&rest[1..]);
+                    } else if rest.starts_with(close.as_str()) {
+                        i += close.len();
+                        strings.push(string_start..i);
+                        self.state = LexState::Code;
+                    } else {
+                        i += next_char_len(rest);
+                    }
+                }
+                LexState::Code => {
+                    let at_start = line[..i].trim().is_empty();
+                    if !syntax.trailing && !at_start {
+                        break;
+                    }
+
+                    if let Some(index) = syntax.block.iter().position(|b| rest.starts_with(b.open))
+                    {
+                        let block = &syntax.block[index];
+                        if syntax.docstrings && !(at_start && self.docstring_allowed) {
+                            string_start = i;
+                            code.push_str(block.open);
+                            self.state = LexState::Str {
+                                close: block.close.to_string(),
+                                escapes: true,
+                                multiline: true,
+                            };
+                            i += block.open.len();
+                            continue;
+                        }
+
+                        docstring = syntax.docstrings;
+                        block_open = i;
+                        i += syntax.block[index].open.len();
+                        block_start = i;
+                        block_continued = false;
+                        block_trailing = !at_start;
+                        self.state = LexState::Block { index, depth: 1 };
+                        continue;
+                    }
+
+                    if let Some(leader) = syntax.line.iter().find(|l| rest.starts_with(**l)) {
+                        spans.push(CommentSpan {
+                            text: &line[i + leader.len()..],
+                            style: CommentStyle::Line(leader),
+                            continued: false,
+                            open_at_end: false,
+                            trailing: !at_start,
+                            start: i,
+                        });
+                        i = bytes.len();
+                        continue;
+                    }
+
+                    if syntax.raw_strings
+                        && let Some((close, len)) = raw_string_open(line, i)
+                    {
+                        string_start = i;
+                        code.push_str(&rest[..len]);
+                        self.state = LexState::Str {
+                            close,
+                            escapes: false,
+                            multiline: false,
+                        };
+                        i += len;
+                        continue;
+                    }
+
+                    if syntax.char_literals && bytes[i] == b'\'' {
+                        let len = char_literal_len(rest);
+                        code.push_str(&rest[..len]);
+                        i += len;
+                        continue;
+                    }
+
+                    if let Some(delim) = syntax.strings.iter().find(|d| rest.starts_with(**d)) {
+                        string_start = i;
+                        code.push_str(delim);
+                        self.state = LexState::Str {
+                            close: delim.to_string(),
+                            escapes: true,
+                            multiline: false,
+                        };
+                        i += delim.len();
+                        continue;
+                    }
+
+                    let len = next_char_len(rest);
+                    code.push_str(&rest[..len]);
+                    i += len;
+                }
+            }
+        }
+
+        match &self.state {
+            LexState::Block { index, .. } => {
+                let start = block_start.min(line.len());
+                spans.push(CommentSpan {
+                    text: &line[start..],
+                    style: CommentStyle::Block(syntax.block[*index].close),
+                    continued: block_continued,
+                    open_at_end: true,
+                    trailing: block_trailing,
+                    start: block_open,
+                });
+            }
+            LexState::Str { multiline, .. } => {
+                strings.push(string_start..line.len());
+                if !syntax.multiline_strings && !multiline {
+                    self.state = LexState::Code;
+                }
+            }
+            LexState::Code => {}
+        }
+
+        if syntax.docstrings {
+            self.track_docstring_position(code.trim(), docstring);
+        }
+
+        LexedLine {
+            comments: spans,
+            strings,
+        }
+    }
+}
+
+impl CommentLexer {
+    /// Work out from a line's code whether the next statement may be a
+    /// docstring: only directly after a `def` or `class` header ending in `:`
+    fn track_docstring_position(&mut self, code: &str, docstring: bool) {
+        if docstring {
+            self.docstring_allowed = false;
+            return;
+        }
+        // Blank and comment-only lines don't count as statements
+        if code.is_empty() {
+            return;
+        }
+
+        self.docstring_allowed = false;
+        let header = ["def ", "async def ", "class "]
+            .iter()
+            .any(|keyword| code.starts_with(keyword));
+        let Some(depth) = self.header_depth.take().or(header.then_some(0)) else {
+            return;
+        };
+
+        let opened = code.matches(['(', '[', '{']).count();
+        let closed = code.matches([')', ']', '}']).count();
+        let depth = (depth + opened).saturating_sub(closed);
+        if depth > 0 {
+            self.header_depth = Some(depth);
+        } else {
+            self.docstring_allowed = code.ends_with(':');
+        }
+    }
+}
+
+/// Length in bytes of the first character of `rest` (at least 1)
+fn next_char_len(rest: &str) -> usize {
+    rest.chars().next().map_or(1, char::len_utf8)
+}
+
+/// Detect a Rust raw string opener (`r"`, `r#"`, `r##"`..., or the same with
+/// a `b` prefix) at `i`, returning the closing delimiter and the opener length
+fn raw_string_open(line: &str, i: usize) -> Option<(String, usize)> {
+    let bytes = line.as_bytes();
+    if i > 0 && (bytes[i - 1].is_ascii_alphanumeric() || bytes[i - 1] == b'_') {
+        return None;
+    }
+
+    let prefix = usize::from(bytes[i] == b'b');
+    if bytes.get(i + prefix) != Some(&b'r') {
+        return None;
+    }
+
+    let hashes = bytes[i + prefix + 1..]
+        .iter()
+        .take_while(|&&b| b == b'#')
+        .count();
+    if bytes.get(i + prefix + 1 + hashes) != Some(&b'"') {
+        return None;
+    }
+
+    Some((format!("\"{}", "#".repeat(hashes)), prefix + hashes + 2))
+}
+
+/// Length in bytes to skip for a `'` that may start a char literal
+///
+/// `'a'` and `'\n'` are skipped whole; anything else (a lifetime or a prime
+/// such as `x'`) only skips the quote itself.
+fn char_literal_len(rest: &str) -> usize {
+    let mut chars = rest.char_indices().skip(1);
+    match chars.next() {
+        Some((_, '\\')) => rest[2..]
+            .find('\'')
+            .map(|idx| idx + 3)
+            .unwrap_or(rest.len()),
+        Some((_, c)) => match chars.next() {
+            Some((idx, '\'')) if c != '\'' => idx + 1,
+            _ => 1,
+        },
+        None => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts<'a>(lexer: &mut CommentLexer, line: &'a str) -> Vec<&'a str> {
+        lexer
+            .lex(line)
+            .comments
+            .into_iter()
+            .map(|s| s.text)
+            .collect()
+    }
+
+    #[test]
+    fn test_syntax_for_path() {
+        assert_eq!(syntax_for_path(Path::new("src/main.rs")).name, "Rust");
+        assert_eq!(syntax_for_path(Path::new("app.PY")).name, "Python");
+        assert_eq!(syntax_for_path(Path::new("query.sql")).name, "SQL");
+        assert_eq!(syntax_for_path(Path::new("Makefile")).name, "Shell-like");
+        assert_eq!(syntax_for_path(Path::new("notes.txt")).name, "Generic");
+    }
+
+    #[test]
+    fn test_trailing_and_string_comments() {
+        let mut lexer = CommentLexer::new(&RUST);
+        assert_eq!(
+            texts(&mut lexer, "foo(); // TODO: later"),
+            vec![" TODO: later"]
+        );
+        assert!(texts(&mut lexer, r#"let s = "// TODO: not a comment";"#).is_empty());
+        assert!(texts(&mut lexer, "#[allow(dead_code)] // keep").len() == 1);
+        assert!(texts(&mut lexer, r##"let s = r#"a " // no"#;"##).is_empty());
+        assert_eq!(texts(&mut lexer, r#"let b = br"\"; // yes"#), vec![" yes"]);
+        assert!(texts(&mut lexer, r##"let b = br#"" // TODO"#;"##).is_empty());
+        assert!(texts(&mut lexer, r#"let s = b"\" // no";"#).is_empty());
+        assert_eq!(
+            texts(&mut lexer, "fn f<'a>(c: char) {} // yes"),
+            vec![" yes"]
+        );
+        assert!(texts(&mut lexer, "let q = '\"'; let s = \"x\";").is_empty());
+        assert_eq!(texts(&mut lexer, "let s = \"─é\"; // ok"), vec![" ok"]);
+
+        let lexed = lexer.lex(r#"f("todo!()", x); g("a"#);
+        assert_eq!(lexed.strings, vec![2..11, 19..21]);
+        assert!(lexed.in_string(5) && !lexed.in_string(14));
+        assert!(lexer.lex("still in string\"").in_string(0));
+    }
+
+    #[test]
+    fn test_hash_not_comment_in_rust_or_strings() {
+        let mut rust = CommentLexer::new(&RUST);
+        assert!(rust.lex("#[derive(Debug)]").comments.is_empty());
+
+        let mut python = CommentLexer::new(&PYTHON);
+        assert!(python.lex("x = '# TODO: string'").comments.is_empty());
+        assert_eq!(
+            texts(&mut python, "x = 1  # TODO: real"),
+            vec![" TODO: real"]
+        );
+    }
+
+    #[test]
+    fn test_python_docstrings() {
+        let mut python = CommentLexer::new(&PYTHON);
+        assert_eq!(
+            texts(&mut python, r#""""TODO: module""""#),
+            vec!["TODO: module"]
+        );
+
+        // Other triple-quoted strings are strings, even across lines
+        let lexed = python.lex(r#"w = """TODO not comment""""#);
+        assert!(lexed.comments.is_empty());
+        assert_eq!(lexed.strings, vec![4..26]);
+        assert!(texts(&mut python, "x = '''").is_empty());
+        assert!(python.lex("TODO: still a string").in_string(0));
+        assert!(texts(&mut python, "'''").is_empty());
+        assert_eq!(
+            texts(&mut python, "y = 1  # TODO: real"),
+            vec![" TODO: real"]
+        );
+
+        // Docstrings directly after a def or class header, even a long one
+        assert!(texts(&mut python, "def f(").is_empty());
+        assert!(texts(&mut python, "    a: int = (1),").is_empty());
+        assert!(texts(&mut python, ") -> None:").is_empty());
+        assert_eq!(texts(&mut python, "    # a comment").len(), 1);
+        assert_eq!(
+            texts(&mut python, r#"    """FIXME: docstring"#),
+            vec!["FIXME: docstring"]
+        );
+        assert_eq!(texts(&mut python, r#"    more""""#), vec!["    more"]);
+        assert!(texts(&mut python, r#"    """TODO: not a docstring""""#).is_empty());
+
+        assert!(texts(&mut python, "class A(Base):").is_empty());
+        assert_eq!(texts(&mut python, "    '''NOTE: x'''"), vec!["NOTE: x"]);
+        assert!(texts(&mut python, "def g(): return '''TODO'''").is_empty());
+        assert!(texts(&mut python, "z = '''TODO'''").is_empty());
+    }
+
+    #[test]
+    fn test_block_comments_across_lines() {
+        let mut lexer = CommentLexer::new(&C_LIKE);
+        let first = lexer.lex("int x; /* TODO: start").comments;
+        assert_eq!(first[0].text, " TODO: start");
+        assert_eq!(first[0].start, 7);
+        assert!(first[0].open_at_end && first[0].trailing);
+
+        let second = lexer.lex(" * middle */ int y;").comments;
+        assert_eq!(second[0].text, " * middle ");
+        assert!(second[0].continued && !second[0].open_at_end);
+
+        assert!(lexer.lex("int z;").comments.is_empty());
+    }
+
+    #[test]
+    fn test_other_comment_styles() {
+        let mut sql = CommentLexer::new(&SQL);
+        assert_eq!(texts(&mut sql, "SELECT 1; -- TODO: x"), vec![" TODO: x"]);
+
+        let mut lua = CommentLexer::new(&LUA);
+        assert_eq!(
+            texts(&mut lua, "--[[ TODO: block ]]"),
+            vec![" TODO: block "]
+        );
+
+        let mut haskell = CommentLexer::new(&HASKELL);
+        assert_eq!(
+            texts(&mut haskell, "{- outer {- inner -} TODO -}"),
+            vec![" outer {- inner -} TODO "]
+        );
+
+        let mut lisp = CommentLexer::new(&LISP);
+        assert_eq!(texts(&mut lisp, "(foo) ; TODO: x"), vec![" TODO: x"]);
+
+        let mut erlang = CommentLexer::new(&ERLANG);
+        assert_eq!(texts(&mut erlang, "% TODO: x"), vec![" TODO: x"]);
+    }
+
+    #[test]
+    fn test_generic_only_matches_at_line_start() {
+        let mut lexer = CommentLexer::new(&GENERIC);
+        assert_eq!(texts(&mut lexer, "  # TODO: x"), vec![" TODO: x"]);
+        assert!(lexer.lex("see issue #12 for details").comments.is_empty());
+    }
+}
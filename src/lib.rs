@@ -8,7 +8,8 @@
 //! - Extract all tech debt comments from a codebase with context
 //! - Calculate "debt age" using git blame
 //! - Group and categorize debt by author, file, severity, and age
-//! - Output reports in multiple formats (terminal, markdown, JSON)
+//! - Output reports in multiple formats (terminal, markdown, JSON, SARIF, CSV,
+//!   HTML), or with user-defined templates
 //! - Language-agnostic (works with any codebase)
 //!
 //! # Example
@@ -27,13 +28,25 @@
 //! let report = models::DebtReport::new(markers, Path::new(".").to_path_buf());
 //! ```
 
+pub mod baseline;
+pub mod cache;
 pub mod cli;
 pub mod config;
+pub mod diff;
 pub mod filters;
+pub mod fingerprint;
 pub mod git;
+pub mod history;
+pub mod hotspots;
+pub mod html;
+pub mod languages;
 pub mod models;
+pub mod policy;
 pub mod reporter;
 pub mod scanner;
+pub mod scoring;
+pub mod stream;
+pub mod template;
 
 // Re-export commonly used types
-pub use models::{Config, DebtMarker, DebtReport, GitBlameInfo};
+pub use models::{Config, DebtMarker, DebtReport, GitBlameInfo, MarkerKind, Severity};
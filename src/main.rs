@@ -1,12 +1,29 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::Utc;
 use clap::Parser;
-use fossil::{cli, config, filters, git, models, reporter, scanner};
+use fossil::baseline::{self, Baseline, ScanLocation};
+use fossil::history::{self, HistoryStore, Snapshot};
+use fossil::{
+    cache, cli, config, diff, filters, git, hotspots, models, policy, reporter, scanner, scoring,
+    stream,
+};
+use git2::Repository;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 fn main() -> Result<()> {
     let cli = cli::Cli::parse();
 
     match cli.command {
         cli::Commands::Scan(args) => scan_command(args)?,
+        cli::Commands::Cache(args) => cache_command(args)?,
+        cli::Commands::History(args) => history_command(args)?,
+        cli::Commands::Diff(args) => diff_command(args)?,
+        cli::Commands::Baseline(args) => baseline_command(args)?,
+        cli::Commands::Hotspots(args) => hotspots_command(args)?,
     }
 
     Ok(())
@@ -19,35 +36,118 @@ fn scan_command(args: cli::ScanArgs) -> Result<()> {
     }
 
     // Load configuration
-    let config =
+    let mut config =
         config::load_config(args.config.as_deref()).context("Failed to load configuration")?;
 
+    // CLI flags override blame settings from the config file
+    if let Some(workers) = args.blame_workers {
+        config.blame.workers = workers;
+    }
+    if args.no_track_copies {
+        config.blame.track_copies = false;
+    }
+    if args.no_cache {
+        config.blame.cache = false;
+    }
+    if args.origin {
+        config.blame.origin = true;
+    }
+    if args.churn {
+        config.scoring.churn = true;
+    }
+
     if args.verbose {
         println!("Using markers: {:?}", config.markers);
     }
 
+    if matches!(args.format, cli::OutputFormat::Ndjson) && !args.count_only {
+        if args.sort_by.is_some() || args.sort_order.is_some() || !args.group_by.is_empty() {
+            bail!(
+                "NDJSON output is written as files are scanned, so it can't be sorted or grouped"
+            );
+        }
+        let violations = stream_scan(&args, &config)?;
+        return exit_on_count(violations, &args);
+    }
+
+    let prefix = git::repo_prefix(&args.path);
+    let (mut report, violations) = scan_report(&args, &config, &prefix)?;
+
+    if args.verbose {
+        println!("Generating report with {} markers", report.total_count);
+    }
+
+    // Generate report
+    let sort_by = args.sort_by.unwrap_or(models::SortKey::Age);
+    let sort_order = args.sort_order.unwrap_or_else(|| sort_by.default_order());
+    report.sort(sort_by, sort_order);
+
+    // Output report
+    reporter::generate_report(
+        &report,
+        args.format,
+        args.output.as_deref(),
+        &reporter::ReportOptions {
+            top_n: args.top,
+            sort_by,
+            sort_order,
+            group_by: &args.group_by,
+            count_only: args.count_only,
+            template: args.template.as_deref(),
+            columns: &args.columns,
+            junit_fail_on: args.junit_fail_on,
+            violations: &violations,
+            prefix: &prefix,
+        },
+    )
+    .context("Failed to generate report")?;
+
+    exit_on_violations(&violations, &args)
+}
+
+/// Scan, blame and score markers into a report, along with any `[policy]`
+/// violations
+///
+/// The policy gates every marker not in the baseline, so with a policy set
+/// the view filters (`--type`, `--author`, `--min-score`, ...) only narrow
+/// the report after it has been checked.
+fn scan_report(
+    args: &cli::ScanArgs,
+    config: &models::Config,
+    prefix: &Path,
+) -> Result<(models::DebtReport, Vec<policy::Violation>)> {
     // Scan directory for markers
     let mut markers =
-        scanner::scan_directory(&args.path, &config).context("Failed to scan directory")?;
+        scanner::scan_directory(&args.path, config).context("Failed to scan directory")?;
 
     if args.verbose {
         println!("Found {} markers before filtering", markers.len());
     }
 
-    // Apply type filter first (before git enrichment to reduce work)
-    if let Some(ref marker_type) = args.marker_type {
-        if args.verbose {
-            println!("Filtering by type: {}", marker_type);
-        }
-        markers = filters::filter_by_type(markers, marker_type);
+    let repo = git::get_repository(&args.path)?;
+
+    // Drop accepted debt first so later stages only see new markers
+    if let Some(ref baseline_path) = args.baseline {
+        let baseline = Baseline::load(baseline_path)?;
+        let renames = baseline_renames(repo.as_ref(), &baseline);
+        let location = ScanLocation {
+            scan_path: &args.path,
+            prefix,
+            renames: &renames,
+        };
+        markers = baseline.new_markers(markers, location);
         if args.verbose {
-            println!("Markers after type filter: {}", markers.len());
+            println!("Markers not in baseline: {}", markers.len());
         }
     }
 
-    // Enrich with git blame information
-    let repo = git::get_repository(&args.path)?;
+    // Without a policy, filter early to save blame work
+    let check_policy = !config.policy.is_empty();
+    if !check_policy {
+        markers = filter_before_blame(markers, args, args.verbose);
+    }
 
+    // Enrich with git blame information
     if args.verbose {
         if repo.is_some() {
             println!("Git repository detected, enriching with blame data...");
@@ -56,39 +156,543 @@ fn scan_command(args: cli::ScanArgs) -> Result<()> {
         }
     }
 
-    git::enrich_markers_batch(&mut markers, repo.as_ref())?;
+    git::enrich_markers_batch(&mut markers, repo.as_ref(), &config.blame)?;
+
+    let churn = churn_counts(repo.as_ref(), config)?;
+    scoring::score_markers(
+        &mut markers,
+        &config.scoring,
+        churn.as_ref(),
+        &args.path,
+        prefix,
+    )?;
 
-    // Apply filters that require git data
+    // Check the configured policy before the view filters
+    let gated = models::DebtReport::new(markers, args.path.clone());
+    let violations = policy::evaluate(&gated, config, prefix)?;
+
+    let mut markers = gated.markers;
+    if check_policy {
+        markers = filter_before_blame(markers, args, args.verbose);
+    }
+    markers = filter_after_blame(markers, args, args.verbose)?;
+
+    Ok((
+        models::DebtReport::new(markers, args.path.clone()),
+        violations,
+    ))
+}
+
+/// Scan with `--format=ndjson`, writing markers as soon as they're blamed
+/// instead of collecting them into a report
+///
+/// Policy violations are written to stderr as each file is checked, before
+/// the view filters, as in [`scan_report`]. Returns how many there were.
+fn stream_scan(args: &cli::ScanArgs, config: &models::Config) -> Result<usize> {
+    let repo = git::get_repository(&args.path)?;
+    let prefix = git::repo_prefix(&args.path);
+
+    let baseline = args.baseline.as_deref().map(Baseline::load).transpose()?;
+    let renames = baseline
+        .as_ref()
+        .map(|baseline| baseline_renames(repo.as_ref(), baseline))
+        .unwrap_or_default();
+    let location = ScanLocation {
+        scan_path: &args.path,
+        prefix: &prefix,
+        renames: &renames,
+    };
+    let mut matcher = baseline.as_ref().map(|baseline| baseline.matcher(location));
+
+    let churn = churn_counts(repo.as_ref(), config)?;
+    let check_policy = !config.policy.is_empty();
+    // Marker violations go to stderr as each file finishes; only the count and
+    // the totals of the gated markers are kept
+    let gate = Mutex::new((
+        policy::ViolationWriter::new(io::stderr(), args.policy_format),
+        models::DebtReport::empty(args.path.clone()),
+    ));
+
+    let out: Box<dyn Write + Send> = match &args.output {
+        Some(path) => {
+            Box::new(BufWriter::new(File::create(path).with_context(|| {
+                format!("Failed to write output to {}", path.display())
+            })?))
+        }
+        None => Box::new(BufWriter::new(io::stdout())),
+    };
+
+    stream::stream_ndjson(
+        &args.path,
+        config,
+        repo.as_ref(),
+        |mut markers| {
+            if let Some(matcher) = &mut matcher {
+                markers = matcher.new_markers(markers);
+            }
+            if check_policy {
+                markers
+            } else {
+                filter_before_blame(markers, args, false)
+            }
+        },
+        |mut markers| {
+            scoring::score_markers(
+                &mut markers,
+                &config.scoring,
+                churn.as_ref(),
+                &args.path,
+                &prefix,
+            )?;
+            let found = policy::evaluate_markers(&markers, &args.path, config, &prefix)?;
+            {
+                let mut gate = gate
+                    .lock()
+                    .map_err(|_| anyhow!("Policy violations lock poisoned"))?;
+                let (violations, totals) = &mut *gate;
+                violations.write(&found)?;
+                for marker in &markers {
+                    totals.count(marker);
+                }
+            }
+
+            if check_policy {
+                markers = filter_before_blame(markers, args, false);
+            }
+            filter_after_blame(markers, args, false)
+        },
+        out,
+    )?;
+
+    if let Some(path) = &args.output {
+        println!("Report written to {}", path.display());
+    }
+
+    let (mut violations, totals) = gate
+        .into_inner()
+        .map_err(|_| anyhow!("Policy violations lock poisoned"))?;
+    violations.write(&policy::evaluate_totals(&totals, config))?;
+    violations.finish()
+}
+
+/// Filters that don't need git data, applied before blame to save work unless
+/// a policy has to see every marker
+fn filter_before_blame(
+    mut markers: Vec<models::DebtMarker>,
+    args: &cli::ScanArgs,
+    verbose: bool,
+) -> Vec<models::DebtMarker> {
+    if let Some(ref marker_type) = args.marker_type {
+        if verbose {
+            println!("Filtering by type: {}", marker_type);
+        }
+        markers = filters::filter_by_type(markers, marker_type);
+        if verbose {
+            println!("Markers after type filter: {}", markers.len());
+        }
+    }
+
+    if let Some(severity) = args.severity {
+        if verbose {
+            println!("Filtering by severity: {}", severity);
+        }
+        markers = filters::filter_by_severity(markers, severity);
+    }
+
+    if let Some(min_severity) = args.min_severity {
+        if verbose {
+            println!("Filtering by minimum severity: {}", min_severity);
+        }
+        markers = filters::filter_by_min_severity(markers, min_severity);
+    }
+
+    if let Some(ref assignee) = args.assignee {
+        if verbose {
+            println!("Filtering by assignee: {}", assignee);
+        }
+        markers = filters::filter_by_assignee(markers, assignee);
+    }
+
+    markers
+}
+
+/// Filters that require git data or scores
+fn filter_after_blame(
+    mut markers: Vec<models::DebtMarker>,
+    args: &cli::ScanArgs,
+    verbose: bool,
+) -> Result<Vec<models::DebtMarker>> {
     if let Some(ref older_than) = args.older_than {
-        if args.verbose {
+        if verbose {
             println!("Filtering by age: {}", older_than);
         }
         markers = filters::filter_by_age(markers, older_than).context("Failed to filter by age")?;
     }
 
     if let Some(ref author) = args.author {
-        if args.verbose {
+        if verbose {
             println!("Filtering by author: {}", author);
         }
         markers = filters::filter_by_author(markers, author);
     }
 
-    if args.verbose {
-        println!("Generating report with {} markers", markers.len());
+    if let Some(min_score) = args.min_score {
+        if verbose {
+            println!("Filtering by minimum score: {}", min_score);
+        }
+        markers = scoring::filter_by_min_score(markers, min_score);
     }
 
-    // Generate report
-    let report = models::DebtReport::new(markers, args.path.clone());
+    Ok(markers)
+}
 
-    // Output report
-    reporter::generate_report(
-        &report,
+/// Recent commit counts per file, if churn scoring is enabled
+fn churn_counts(
+    repo: Option<&Repository>,
+    config: &models::Config,
+) -> Result<Option<HashMap<PathBuf, usize>>> {
+    let (Some(repo), true) = (repo, config.scoring.churn) else {
+        return Ok(None);
+    };
+
+    let window = filters::parse_duration(&config.scoring.churn_window)
+        .with_context(|| format!("Invalid churn_window: {}", config.scoring.churn_window))?;
+    let activity = git::file_activity(repo, window)?;
+    Ok(Some(
+        activity
+            .into_iter()
+            .map(|(path, activity)| (path, activity.commits))
+            .collect(),
+    ))
+}
+
+/// Print `[policy]` violations to stderr and exit with status 1 if there are any
+fn exit_on_violations(violations: &[policy::Violation], args: &cli::ScanArgs) -> Result<()> {
+    let mut writer = policy::ViolationWriter::new(io::stderr(), args.policy_format);
+    writer.write(violations)?;
+    exit_on_count(writer.finish()?, args)
+}
+
+/// Exit with status 1 if any `[policy]` violations were written
+fn exit_on_count(count: usize, args: &cli::ScanArgs) -> Result<()> {
+    if count > 0 {
+        if args.verbose {
+            eprintln!("{} policy violations", count);
+        }
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn cache_command(args: cli::CacheArgs) -> Result<()> {
+    match args.command {
+        cli::CacheCommand::Clear { path } => {
+            // The cache lives at the repository root; fall back to the given path
+            let root = git::repo_root(&path)?;
+
+            let removed = cache::clear_cache(&root).context("Failed to clear cache")?;
+            println!(
+                "Removed {} cache entries from {}",
+                removed,
+                root.join(cache::CACHE_DIR).display()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn history_command(args: cli::HistoryArgs) -> Result<()> {
+    match args.command {
+        cli::HistoryCommand::Init { path } => {
+            let root = git::repo_root(&path)?;
+            let (store, created) = HistoryStore::init(&root)?;
+            if created {
+                println!("Initialized debt history in {}", store.dir().display());
+            } else {
+                println!(
+                    "Debt history already initialized in {}",
+                    store.dir().display()
+                );
+            }
+        }
+        cli::HistoryCommand::Snapshot { path, config } => {
+            let store = HistoryStore::open(&git::repo_root(&path)?)?;
+            let config =
+                config::load_config(config.as_deref()).context("Failed to load configuration")?;
+
+            let mut markers =
+                scanner::scan_directory(&path, &config).context("Failed to scan directory")?;
+            let repo = git::get_repository(&path)?;
+            git::enrich_markers_batch(&mut markers, repo.as_ref(), &config.blame)?;
+
+            // Scored as `fossil scan` would, so the debt index is comparable
+            let churn = churn_counts(repo.as_ref(), &config)?;
+            let prefix = git::repo_prefix(&path);
+            scoring::score_markers(
+                &mut markers,
+                &config.scoring,
+                churn.as_ref(),
+                &path,
+                &prefix,
+            )?;
+
+            let commit = repo.as_ref().and_then(git::head_commit);
+            let report = models::DebtReport::new(markers, path);
+            let snapshot = Snapshot::new(report, Utc::now(), commit);
+            let file = store.save(&snapshot)?;
+            println!(
+                "Recorded snapshot {} ({} markers) in {}",
+                snapshot.id(),
+                snapshot.report.total_count,
+                file.display()
+            );
+        }
+        cli::HistoryCommand::Backfill {
+            path,
+            since,
+            every,
+            config,
+        } => {
+            let store = HistoryStore::open(&git::repo_root(&path)?)?;
+            let config =
+                config::load_config(config.as_deref()).context("Failed to load configuration")?;
+            let repo = git::get_repository(&path)?
+                .context("History backfill requires a git repository")?;
+
+            let since = filters::parse_duration(&since)
+                .with_context(|| format!("Invalid duration: {}", since))?;
+            let every = filters::parse_duration(&every)
+                .with_context(|| format!("Invalid duration: {}", every))?;
+
+            // Scan the same directory in each past tree
+            let workdir = repo
+                .workdir()
+                .context("Repository has no working directory")?;
+            let prefix = git::relative_to_workdir(workdir, &path).unwrap_or_default();
+
+            let commits = history::backfill_commits(&repo, since, every)?;
+            println!("Backfilling {} snapshots...", commits.len());
+            for commit_id in commits {
+                let snapshot = history::snapshot_commit(&repo, commit_id, &prefix, &path, &config)?;
+                store.save(&snapshot)?;
+                println!(
+                    "  {} ({} markers)",
+                    snapshot.id(),
+                    snapshot.report.total_count
+                );
+            }
+        }
+        cli::HistoryCommand::Show { path, limit } => {
+            let store = HistoryStore::open(&git::repo_root(&path)?)?;
+            let snapshots = store.load_all()?;
+            println!("{}", history::format_trend(&snapshots, limit));
+        }
+        cli::HistoryCommand::Compare { from, to, path } => {
+            let store = HistoryStore::open(&git::repo_root(&path)?)?;
+            let from = store.find(&from)?;
+            let to = store.find(&to)?;
+            println!("{}", history::format_comparison(&from, &to));
+        }
+    }
+
+    Ok(())
+}
+
+fn diff_command(args: cli::DiffArgs) -> Result<()> {
+    let config =
+        config::load_config(args.config.as_deref()).context("Failed to load configuration")?;
+    let repo = git::get_repository(&args.path)?.context("fossil diff requires a git repository")?;
+
+    let (base, head, merge_base) = match (&args.range, &args.base) {
+        (Some(range), _) => diff::parse_range(range),
+        (None, Some(base)) => (base.clone(), "HEAD".to_string(), false),
+        (None, None) => unreachable!("clap requires a range or --base"),
+    };
+
+    let marker_diff = diff::diff_revisions(&repo, &base, &head, merge_base, &config)?;
+
+    reporter::generate_diff_report(
+        &marker_diff,
         args.format,
         args.output.as_deref(),
-        args.top,
-        args.count_only,
+        &args.columns,
     )
     .context("Failed to generate report")?;
 
     Ok(())
 }
+
+fn hotspots_command(args: cli::HotspotsArgs) -> Result<()> {
+    let config =
+        config::load_config(args.config.as_deref()).context("Failed to load configuration")?;
+    let repo =
+        git::get_repository(&args.path)?.context("fossil hotspots requires a git repository")?;
+    let since = filters::parse_duration(&args.since)
+        .with_context(|| format!("Invalid duration: {}", args.since))?;
+
+    // Score debt without churn; hotspots weigh it against churn themselves
+    let mut markers =
+        scanner::scan_directory(&args.path, &config).context("Failed to scan directory")?;
+    git::enrich_markers_batch(&mut markers, Some(&repo), &config.blame)?;
+    let prefix = git::repo_prefix(&args.path);
+    scoring::score_markers(&mut markers, &config.scoring, None, &args.path, &prefix)?;
+    let report = models::DebtReport::new(markers, args.path.clone());
+
+    let activity = git::file_activity(&repo, since)?;
+    let mut ranked = hotspots::rank_hotspots(&report, &activity, &prefix);
+    ranked.truncate(args.top);
+
+    reporter::generate_hotspot_report(&ranked, &args.since, args.format, args.output.as_deref())
+        .context("Failed to generate report")?;
+
+    Ok(())
+}
+
+fn baseline_command(args: cli::BaselineArgs) -> Result<()> {
+    let (options, force) = match &args.command {
+        cli::BaselineCommand::Create { options, force } => (options, *force),
+        cli::BaselineCommand::Update { options } | cli::BaselineCommand::Prune { options } => {
+            (options, false)
+        }
+    };
+
+    let config =
+        config::load_config(options.config.as_deref()).context("Failed to load configuration")?;
+    let file = match &options.baseline {
+        Some(file) => file.clone(),
+        None => git::repo_root(&options.path)?.join(baseline::BASELINE_FILE),
+    };
+
+    let markers =
+        scanner::scan_directory(&options.path, &config).context("Failed to scan directory")?;
+    let repo = git::get_repository(&options.path)?;
+    let commit = repo.as_ref().and_then(git::head_commit);
+    let prefix = git::repo_prefix(&options.path);
+
+    match args.command {
+        cli::BaselineCommand::Create { .. } => {
+            if file.exists() && !force {
+                anyhow::bail!(
+                    "Baseline {} already exists; use `fossil baseline update` or pass --force",
+                    file.display()
+                );
+            }
+
+            let location = ScanLocation {
+                scan_path: &options.path,
+                prefix: &prefix,
+                renames: &HashMap::new(),
+            };
+            Baseline::new(&markers, location, commit).save(&file)?;
+            println!(
+                "Wrote baseline of {} markers to {}",
+                markers.len(),
+                file.display()
+            );
+        }
+        cli::BaselineCommand::Update { .. } => {
+            let mut baseline = Baseline::load(&file)?;
+            let renames = baseline_renames(repo.as_ref(), &baseline);
+            let location = ScanLocation {
+                scan_path: &options.path,
+                prefix: &prefix,
+                renames: &renames,
+            };
+            let added = baseline.update(&markers, location, commit);
+            baseline.save(&file)?;
+            println!(
+                "Added {} markers to {} ({} entries)",
+                added,
+                file.display(),
+                baseline.entries.len()
+            );
+        }
+        cli::BaselineCommand::Prune { .. } => {
+            let mut baseline = Baseline::load(&file)?;
+            let renames = baseline_renames(repo.as_ref(), &baseline);
+            let location = ScanLocation {
+                scan_path: &options.path,
+                prefix: &prefix,
+                renames: &renames,
+            };
+            let removed = baseline.prune(&markers, location, commit);
+            baseline.save(&file)?;
+            println!(
+                "Removed {} resolved markers from {} ({} entries)",
+                removed,
+                file.display(),
+                baseline.entries.len()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Files renamed since a baseline was written, when git can tell
+fn baseline_renames(repo: Option<&Repository>, baseline: &Baseline) -> HashMap<PathBuf, PathBuf> {
+    match (repo, &baseline.commit) {
+        // The baseline commit may be gone (e.g. after a rebase); skip renames then
+        (Some(repo), Some(commit)) => git::renames_since(repo, commit).unwrap_or_default(),
+        _ => HashMap::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn scan_args(args: &[&str]) -> cli::ScanArgs {
+        match cli::Cli::parse_from(["fossil", "scan"].iter().chain(args)).command {
+            cli::Commands::Scan(args) => args,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_policy_ignores_view_filters() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.rs"), "// FIXME: broken\n// NOTE: fine\n").unwrap();
+        let path = dir.path().to_str().unwrap();
+        let config = models::Config {
+            policy: models::PolicyConfig {
+                max_per_type: HashMap::from([("FIXME".to_string(), 0)]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // The FIXME is filtered out of the report but still fails the gate
+        let args = scan_args(&[path, "--type", "NOTE"]);
+        let (report, violations) = scan_report(&args, &config, Path::new("")).unwrap();
+        assert_eq!(report.total_count, 1);
+        assert_eq!(report.markers[0].marker_type, "NOTE");
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, "max_per_type");
+
+        let output = dir.path().join("out.ndjson");
+        let args = scan_args(&[
+            path,
+            "--type",
+            "NOTE",
+            "--format",
+            "ndjson",
+            "--output",
+            output.to_str().unwrap(),
+        ]);
+        assert_eq!(stream_scan(&args, &config).unwrap(), 1);
+        let ndjson = fs::read_to_string(&output).unwrap();
+        assert!(ndjson.contains("NOTE"));
+        assert!(!ndjson.contains("FIXME"));
+
+        // Without a policy the filters apply as before
+        let (report, violations) =
+            scan_report(&args, &models::Config::default(), Path::new("")).unwrap();
+        assert_eq!(report.total_count, 1);
+        assert!(violations.is_empty());
+    }
+}
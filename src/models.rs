@@ -1,7 +1,10 @@
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 /// Represents a single technical debt marker found in code
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,6 +12,14 @@ pub struct DebtMarker {
     /// Type of marker (TODO, FIXME, HACK, XXX, NOTE, etc.)
     pub marker_type: String,
 
+    /// Whether the marker is a comment or a code construct
+    #[serde(default)]
+    pub kind: MarkerKind,
+
+    /// How serious the marker is, from the `[severity]` mapping
+    #[serde(default)]
+    pub severity: Severity,
+
     /// Path to the file containing the marker
     pub file_path: PathBuf,
 
@@ -18,6 +29,22 @@ pub struct DebtMarker {
     /// The actual line content containing the marker
     pub line_content: String,
 
+    /// Text following the marker, with any assignee prefix removed
+    #[serde(default)]
+    pub description: String,
+
+    /// Assignee parsed from `TODO(alice):` or `FIXME[bob]` forms
+    #[serde(default)]
+    pub assignee: Option<String>,
+
+    /// Issue references found in the description (`#123`, `JIRA-456`, URLs)
+    #[serde(default)]
+    pub issue_refs: Vec<String>,
+
+    /// Raw lines that continue the marker's comment (merged into `description`)
+    #[serde(default)]
+    pub continuation_lines: Vec<String>,
+
     /// Lines of code before the marker for context
     pub context_before: Vec<String>,
 
@@ -26,6 +53,108 @@ pub struct DebtMarker {
 
     /// Git blame information if available
     pub git_info: Option<GitBlameInfo>,
+
+    /// Stable identity of the marker across edits (see `fingerprint`)
+    #[serde(default)]
+    pub fingerprint: String,
+
+    /// Debt score from severity, age and churn (see `scoring`)
+    #[serde(default)]
+    pub score: f64,
+}
+
+#[cfg(test)]
+impl DebtMarker {
+    /// A `// <marker_type>: something` comment marker for tests, without git
+    /// info; override fields with struct update syntax
+    pub fn test(file: impl Into<PathBuf>, line_number: usize, marker_type: &str) -> Self {
+        Self {
+            marker_type: marker_type.to_string(),
+            kind: MarkerKind::Comment,
+            severity: Severity::default(),
+            file_path: file.into(),
+            line_number,
+            line_content: format!("// {}: something", marker_type),
+            description: "something".to_string(),
+            assignee: None,
+            issue_refs: vec![],
+            continuation_lines: vec![],
+            context_before: vec![],
+            context_after: vec![],
+            git_info: None,
+            fingerprint: String::new(),
+            score: 0.0,
+        }
+    }
+}
+
+/// Where a debt marker was found
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarkerKind {
+    /// A comment marker such as `// TODO:`
+    #[default]
+    Comment,
+    /// A code construct such as `todo!()`, `# noqa` or `@Deprecated`
+    Code,
+}
+
+/// How serious a debt marker is; ordered from least to most severe
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Low,
+    /// Severity of marker types missing from the `[severity]` mapping
+    #[default]
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    /// All severities, most severe first
+    pub const ALL: [Severity; 5] = [
+        Severity::Critical,
+        Severity::High,
+        Severity::Medium,
+        Severity::Low,
+        Severity::Info,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Severity::ALL
+            .into_iter()
+            .find(|severity| severity.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                format!(
+                    "unknown severity `{}` (expected critical, high, medium, low or info)",
+                    s
+                )
+            })
+    }
 }
 
 /// Git blame information for a debt marker
@@ -43,23 +172,241 @@ pub struct GitBlameInfo {
     /// When the line was committed
     pub commit_time: DateTime<Utc>,
 
-    /// Age in days since commit
+    /// Age in days since commit (since `introduced_at` in origin mode)
     pub age_days: i64,
+
+    /// Author of the commit that first introduced the marker text (origin mode)
+    #[serde(default)]
+    pub introduced_by: Option<String>,
+
+    /// When the marker text was first introduced (origin mode)
+    #[serde(default)]
+    pub introduced_at: Option<DateTime<Utc>>,
+
+    /// Abbreviated hash of the commit that introduced the marker text (origin mode)
+    #[serde(default)]
+    pub introduced_commit: Option<String>,
 }
 
 impl GitBlameInfo {
     /// Format age as human-readable string (e.g., "347d", "2m", "1y")
     pub fn age_display(&self) -> String {
-        if self.age_days < 30 {
-            format!("{}d", self.age_days)
-        } else if self.age_days < 365 {
-            format!("{}m", self.age_days / 30)
-        } else {
-            format!("{}y", self.age_days / 365)
+        format_age(self.age_days)
+    }
+}
+
+#[cfg(test)]
+impl GitBlameInfo {
+    /// Blame info for tests, committed by `author` `age_days` ago
+    pub fn test(author: &str, age_days: i64) -> Self {
+        Self {
+            author: author.to_string(),
+            author_email: format!("{}@example.com", author.to_lowercase()),
+            commit_hash: "abc1234".to_string(),
+            commit_time: Utc::now(),
+            age_days,
+            introduced_by: None,
+            introduced_at: None,
+            introduced_commit: None,
+        }
+    }
+}
+
+/// Format an age in days as a human-readable string (see
+/// [`GitBlameInfo::age_display`])
+pub fn format_age(days: i64) -> String {
+    if days < 30 {
+        format!("{}d", days)
+    } else if days < 365 {
+        format!("{}m", days / 30)
+    } else {
+        format!("{}y", days / 365)
+    }
+}
+
+/// Parse one of `variants` by name, ignoring case
+fn parse_variant<T: Copy>(
+    variants: &[T],
+    name: fn(&T) -> &'static str,
+    s: &str,
+    what: &str,
+) -> Result<T, String> {
+    variants
+        .iter()
+        .find(|variant| name(variant).eq_ignore_ascii_case(s.trim()))
+        .copied()
+        .ok_or_else(|| {
+            let names: Vec<&str> = variants.iter().map(name).collect();
+            format!("unknown {} `{}` (expected {})", what, s, names.join(", "))
+        })
+}
+
+/// What markers are sorted by (see [`DebtReport::sort`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// Age (oldest first by default)
+    Age,
+    /// File path and line
+    File,
+    /// Git author
+    Author,
+    /// Marker type
+    Type,
+    /// Debt score (highest first by default)
+    Score,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 5] = [
+        SortKey::Age,
+        SortKey::File,
+        SortKey::Author,
+        SortKey::Type,
+        SortKey::Score,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortKey::Age => "age",
+            SortKey::File => "file",
+            SortKey::Author => "author",
+            SortKey::Type => "type",
+            SortKey::Score => "score",
+        }
+    }
+
+    /// Direction used when none is given: largest first for age and score,
+    /// alphabetical otherwise
+    pub fn default_order(self) -> SortOrder {
+        match self {
+            SortKey::Age | SortKey::Score => SortOrder::Desc,
+            SortKey::File | SortKey::Author | SortKey::Type => SortOrder::Asc,
+        }
+    }
+
+    /// Name for report headings
+    pub fn label(self) -> &'static str {
+        match self {
+            SortKey::Age => "Age",
+            SortKey::File => "File",
+            SortKey::Author => "Author",
+            SortKey::Type => "Type",
+            SortKey::Score => "Score",
         }
     }
 }
 
+impl fmt::Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_variant(&SortKey::ALL, SortKey::as_str, s, "sort key")
+    }
+}
+
+/// Sort direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub const ALL: [SortOrder; 2] = [SortOrder::Asc, SortOrder::Desc];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        }
+    }
+}
+
+impl fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_variant(&SortOrder::ALL, SortOrder::as_str, s, "sort order")
+    }
+}
+
+/// What markers are grouped by (see [`DebtReport::group_by`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupKey {
+    /// File path
+    File,
+    /// Directory containing the file
+    Dir,
+    /// Git author
+    Author,
+    /// Marker type
+    Type,
+    /// Marker severity
+    Severity,
+    /// Assignee, e.g. `alice` in `TODO(alice):`
+    Assignee,
+}
+
+impl GroupKey {
+    pub const ALL: [GroupKey; 6] = [
+        GroupKey::File,
+        GroupKey::Dir,
+        GroupKey::Author,
+        GroupKey::Type,
+        GroupKey::Severity,
+        GroupKey::Assignee,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GroupKey::File => "file",
+            GroupKey::Dir => "dir",
+            GroupKey::Author => "author",
+            GroupKey::Type => "type",
+            GroupKey::Severity => "severity",
+            GroupKey::Assignee => "assignee",
+        }
+    }
+
+    /// Name for report headings
+    pub fn label(self) -> &'static str {
+        match self {
+            GroupKey::File => "File",
+            GroupKey::Dir => "Directory",
+            GroupKey::Author => "Author",
+            GroupKey::Type => "Type",
+            GroupKey::Severity => "Severity",
+            GroupKey::Assignee => "Assignee",
+        }
+    }
+}
+
+impl fmt::Display for GroupKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for GroupKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_variant(&GroupKey::ALL, GroupKey::as_str, s, "group key")
+    }
+}
+
 /// Complete report of technical debt found in a codebase
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DebtReport {
@@ -72,12 +419,28 @@ pub struct DebtReport {
     /// Count of markers by type
     pub by_type: HashMap<String, usize>,
 
+    /// Count of markers by severity
+    #[serde(default)]
+    pub by_severity: HashMap<Severity, usize>,
+
     /// Count of markers by author
     pub by_author: HashMap<String, usize>,
 
     /// Count of markers by file
     pub by_file: HashMap<PathBuf, usize>,
 
+    /// Sum of marker scores by file
+    #[serde(default)]
+    pub score_by_file: HashMap<PathBuf, f64>,
+
+    /// Sum of marker scores by directory (not including subdirectories)
+    #[serde(default)]
+    pub score_by_dir: HashMap<PathBuf, f64>,
+
+    /// Technical debt index: the sum of all marker scores
+    #[serde(default)]
+    pub debt_index: f64,
+
     /// Path that was scanned
     pub scan_path: PathBuf,
 
@@ -88,50 +451,187 @@ pub struct DebtReport {
 impl DebtReport {
     /// Create a new report from a collection of markers
     pub fn new(markers: Vec<DebtMarker>, scan_path: PathBuf) -> Self {
-        let total_count = markers.len();
-
-        let mut by_type: HashMap<String, usize> = HashMap::new();
-        let mut by_author: HashMap<String, usize> = HashMap::new();
-        let mut by_file: HashMap<PathBuf, usize> = HashMap::new();
-
+        let mut report = Self::empty(scan_path);
         for marker in &markers {
-            *by_type.entry(marker.marker_type.clone()).or_insert(0) += 1;
-            *by_file.entry(marker.file_path.clone()).or_insert(0) += 1;
-
-            if let Some(ref git_info) = marker.git_info {
-                *by_author.entry(git_info.author.clone()).or_insert(0) += 1;
-            }
+            report.count(marker);
         }
+        report.markers = markers;
+        report
+    }
 
+    /// Create a report with no markers, for totals built up with
+    /// [`DebtReport::count`]
+    pub fn empty(scan_path: PathBuf) -> Self {
         Self {
-            markers,
-            total_count,
-            by_type,
-            by_author,
-            by_file,
+            markers: Vec::new(),
+            total_count: 0,
+            by_type: HashMap::new(),
+            by_severity: HashMap::new(),
+            by_author: HashMap::new(),
+            by_file: HashMap::new(),
+            score_by_file: HashMap::new(),
+            score_by_dir: HashMap::new(),
+            debt_index: 0.0,
             scan_path,
             scan_time: Utc::now(),
         }
     }
 
+    /// Add a marker to the totals without storing it in `markers`
+    pub fn count(&mut self, marker: &DebtMarker) {
+        self.total_count += 1;
+        *self.by_type.entry(marker.marker_type.clone()).or_insert(0) += 1;
+        *self.by_severity.entry(marker.severity).or_insert(0) += 1;
+        *self.by_file.entry(marker.file_path.clone()).or_insert(0) += 1;
+        *self
+            .score_by_file
+            .entry(marker.file_path.clone())
+            .or_insert(0.0) += marker.score;
+
+        *self
+            .score_by_dir
+            .entry(marker_dir(&marker.file_path))
+            .or_insert(0.0) += marker.score;
+
+        if let Some(ref git_info) = marker.git_info {
+            *self.by_author.entry(git_info.author.clone()).or_insert(0) += 1;
+        }
+
+        self.debt_index += marker.score;
+    }
+
+    /// Get markers sorted by score (highest first)
+    pub fn highest_scoring(&self, limit: usize) -> Vec<&DebtMarker> {
+        let mut markers = self.sorted_markers(SortKey::Score, SortOrder::Desc);
+        markers.truncate(limit);
+        markers
+    }
+
     /// Get markers sorted by age (oldest first)
     pub fn oldest_markers(&self, limit: usize) -> Vec<&DebtMarker> {
-        let mut markers_with_age: Vec<&DebtMarker> = self
+        let mut markers = self.sorted_markers(SortKey::Age, SortOrder::Desc);
+        markers.truncate(limit);
+        markers
+    }
+
+    /// Get markers sorted by `key`, leaving out markers without a value for
+    /// it (unblamed markers, for age and author)
+    pub fn sorted_markers(&self, key: SortKey, order: SortOrder) -> Vec<&DebtMarker> {
+        let mut markers: Vec<&DebtMarker> = self
             .markers
             .iter()
-            .filter(|m| m.git_info.is_some())
+            .filter(|m| m.git_info.is_some() || !matches!(key, SortKey::Age | SortKey::Author))
             .collect();
+        markers.sort_by(|a, b| compare_markers(a, b, key, order));
+        markers
+    }
 
-        markers_with_age.sort_by(|a, b| {
-            let age_a = a.git_info.as_ref().unwrap().age_days;
-            let age_b = b.git_info.as_ref().unwrap().age_days;
-            age_b.cmp(&age_a) // Descending order (oldest first)
-        });
+    /// Sort markers by `key`; markers without a value for it go last
+    pub fn sort(&mut self, key: SortKey, order: SortOrder) {
+        self.markers
+            .sort_by(|a, b| compare_markers(a, b, key, order));
+    }
+
+    /// Count markers by each of `keys` in turn, e.g. by directory and then
+    /// type within each directory
+    ///
+    /// Groups are ordered by count, highest first, then by key.
+    pub fn group_by(&self, keys: &[GroupKey]) -> Vec<MarkerGroup> {
+        let markers: Vec<&DebtMarker> = self.markers.iter().collect();
+        group_markers(&markers, keys)
+    }
+}
+
+/// A group of markers in a [`DebtReport::group_by`] summary
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MarkerGroup {
+    /// The markers' value for the group's key, e.g. a directory or author;
+    /// `-` for markers without one
+    pub key: String,
+
+    /// Number of markers in the group
+    pub count: usize,
+
+    /// Sum of the markers' scores
+    pub score: f64,
+
+    /// The markers grouped by the next key, empty for the last key
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<MarkerGroup>,
+}
 
-        markers_with_age.into_iter().take(limit).collect()
+/// Directory a marker's file is in, `.` for the top level
+fn marker_dir(path: &Path) -> PathBuf {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
     }
 }
 
+/// Compare markers by `key`, then by location
+///
+/// Markers without a value for `key` sort after those with one in either
+/// direction.
+fn compare_markers(a: &DebtMarker, b: &DebtMarker, key: SortKey, order: SortOrder) -> Ordering {
+    let directed = |ordering: Ordering| match order {
+        SortOrder::Asc => ordering,
+        SortOrder::Desc => ordering.reverse(),
+    };
+    let known_first = |x: bool, y: bool| y.cmp(&x);
+    let location = a
+        .file_path
+        .cmp(&b.file_path)
+        .then(a.line_number.cmp(&b.line_number));
+
+    let primary = match key {
+        SortKey::Age | SortKey::Author => match (&a.git_info, &b.git_info) {
+            (Some(x), Some(y)) if key == SortKey::Age => directed(x.age_days.cmp(&y.age_days)),
+            (Some(x), Some(y)) => directed(x.author.cmp(&y.author)),
+            (x, y) => known_first(x.is_some(), y.is_some()),
+        },
+        SortKey::File => directed(location),
+        SortKey::Type => directed(a.marker_type.cmp(&b.marker_type)),
+        SortKey::Score => directed(a.score.total_cmp(&b.score)),
+    };
+    primary.then(location)
+}
+
+/// Group markers by the first of `keys`, and each group by the rest
+fn group_markers(markers: &[&DebtMarker], keys: &[GroupKey]) -> Vec<MarkerGroup> {
+    let Some((&key, rest)) = keys.split_first() else {
+        return Vec::new();
+    };
+
+    let mut by_value: BTreeMap<String, Vec<&DebtMarker>> = BTreeMap::new();
+    for &marker in markers {
+        let value = match key {
+            GroupKey::File => marker.file_path.display().to_string(),
+            GroupKey::Dir => marker_dir(&marker.file_path).display().to_string(),
+            GroupKey::Author => marker
+                .git_info
+                .as_ref()
+                .map_or_else(|| "-".to_string(), |info| info.author.clone()),
+            GroupKey::Type => marker.marker_type.clone(),
+            GroupKey::Severity => marker.severity.to_string(),
+            GroupKey::Assignee => marker.assignee.clone().unwrap_or_else(|| "-".to_string()),
+        };
+        by_value.entry(value).or_default().push(marker);
+    }
+
+    let mut groups: Vec<MarkerGroup> = by_value
+        .into_iter()
+        .map(|(key, markers)| MarkerGroup {
+            key,
+            count: markers.len(),
+            score: markers.iter().map(|m| m.score).sum(),
+            groups: group_markers(&markers, rest),
+        })
+        .collect();
+    // Stable, so equal counts stay in key order
+    groups.sort_by_key(|group| Reverse(group.count));
+    groups
+}
+
 /// Configuration for the fossil scanner
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Config {
@@ -147,9 +647,195 @@ pub struct Config {
     #[serde(default = "default_context_lines")]
     pub context_lines: usize,
 
-    /// Optional severity mapping for markers
+    /// Severity of each marker type, merged over the built-in mapping; types
+    /// not listed are medium
+    #[serde(
+        default = "default_severity",
+        deserialize_with = "merge_default_severity"
+    )]
+    pub severity: HashMap<String, Severity>,
+
+    /// Code constructs reported as debt alongside comment markers
+    #[serde(default = "default_code_markers")]
+    pub code_markers: Vec<CodeMarkerConfig>,
+
+    /// Git blame settings
+    #[serde(default)]
+    pub blame: BlameConfig,
+
+    /// Rules checked after a scan; violations make `fossil scan` fail
+    #[serde(default)]
+    pub policy: PolicyConfig,
+
+    /// How markers are scored
+    #[serde(default)]
+    pub scoring: ScoringConfig,
+}
+
+/// Scoring model: severity weight × age factor (× churn factor)
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ScoringConfig {
+    /// Weight of each severity; missing severities use the default weight
+    #[serde(default = "default_severity_weights")]
+    pub weights: HashMap<Severity, f64>,
+
+    /// Age at which a marker's score doubles (the age factor is
+    /// `1 + age / age_scale`), e.g. `"1y"`
+    #[serde(default = "default_age_scale")]
+    pub age_scale: String,
+
+    /// Also multiply by the number of commits that touched the file recently
+    #[serde(default)]
+    pub churn: bool,
+
+    /// How far back commits count towards churn, e.g. `"90d"`
+    #[serde(default = "default_churn_window")]
+    pub churn_window: String,
+}
+
+impl ScoringConfig {
+    /// Weight of a severity
+    pub fn weight(&self, severity: Severity) -> f64 {
+        self.weights
+            .get(&severity)
+            .copied()
+            .unwrap_or_else(|| default_severity_weights()[&severity])
+    }
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self {
+            weights: default_severity_weights(),
+            age_scale: default_age_scale(),
+            churn: false,
+            churn_window: default_churn_window(),
+        }
+    }
+}
+
+/// Limits on debt enforced by `fossil scan`, e.g. to gate CI
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct PolicyConfig {
+    /// Maximum number of markers in total
+    #[serde(default)]
+    pub max_total: Option<usize>,
+
+    /// Maximum technical debt index (sum of marker scores)
+    #[serde(default)]
+    pub max_debt_index: Option<f64>,
+
+    /// Fail if any marker lacks an assignee, e.g. `TODO(alice):`
+    #[serde(default)]
+    pub require_assignee: bool,
+
+    /// Maximum number of markers of each type, e.g. `HACK = 0`
+    #[serde(default)]
+    pub max_per_type: HashMap<String, usize>,
+
+    /// Maximum age of markers of each severity, e.g. `high = "90d"`
+    #[serde(default)]
+    pub max_age: HashMap<Severity, String>,
+
+    /// Marker types that must not appear in some paths
+    #[serde(default)]
+    pub forbidden: Vec<ForbiddenRule>,
+}
+
+impl PolicyConfig {
+    /// Whether no rules are set
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Marker types forbidden in files matching any of `paths`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ForbiddenRule {
+    /// Forbidden marker types
+    pub types: Vec<String>,
+
+    /// Glob patterns relative to the repository root, e.g. `src/core/**`
+    pub paths: Vec<String>,
+}
+
+/// Settings for git blame enrichment
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct BlameConfig {
+    /// Number of files blamed in parallel (0 means one worker per CPU)
+    #[serde(default)]
+    pub workers: usize,
+
+    /// Follow lines copied or moved between files; accurate but slow
+    #[serde(default = "default_track_copies")]
+    pub track_copies: bool,
+
+    /// Reuse blame results from `.fossil/cache` for unchanged files
+    #[serde(default = "default_blame_cache")]
+    pub cache: bool,
+
+    /// Walk history to find the commit that first introduced each marker
     #[serde(default)]
-    pub severity: HashMap<String, String>,
+    pub origin: bool,
+
+    /// Commits whose changes are skipped when attributing lines, in addition
+    /// to those listed in `.git-blame-ignore-revs`
+    #[serde(default)]
+    pub ignore_revs: Vec<String>,
+}
+
+impl Default for BlameConfig {
+    fn default() -> Self {
+        Self {
+            workers: 0,
+            track_copies: default_track_copies(),
+            cache: default_blame_cache(),
+            origin: false,
+            ignore_revs: Vec::new(),
+        }
+    }
+}
+
+/// A code construct reported as debt, e.g. `todo!()` or `# noqa`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CodeMarkerConfig {
+    /// Marker type reported for matches
+    pub name: String,
+
+    /// Regex to match; capture group 1, if present, becomes the description
+    pub pattern: String,
+
+    /// Whether the pattern is matched against code or comment text
+    #[serde(default)]
+    pub scope: CodeMarkerScope,
+
+    /// File extensions the pattern applies to (empty means all files)
+    #[serde(default)]
+    pub extensions: Vec<String>,
+}
+
+/// Which part of a line a code marker pattern is matched against
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CodeMarkerScope {
+    /// Code before any comment on the line
+    #[default]
+    Code,
+    /// Text inside comments
+    Comment,
+}
+
+impl Config {
+    /// Severity of a marker type (matched case-insensitively)
+    pub fn severity_of(&self, marker_type: &str) -> Severity {
+        self.severity.get(marker_type).copied().unwrap_or_else(|| {
+            self.severity
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(marker_type))
+                .map(|(_, severity)| *severity)
+                .unwrap_or_default()
+        })
+    }
 }
 
 impl Default for Config {
@@ -158,7 +844,11 @@ impl Default for Config {
             markers: default_markers(),
             ignored_dirs: default_ignored_dirs(),
             context_lines: default_context_lines(),
-            severity: HashMap::new(),
+            severity: default_severity(),
+            code_markers: default_code_markers(),
+            blame: BlameConfig::default(),
+            policy: PolicyConfig::default(),
+            scoring: ScoringConfig::default(),
         }
     }
 }
@@ -173,6 +863,117 @@ fn default_markers() -> Vec<String> {
     ]
 }
 
+fn default_severity() -> HashMap<String, Severity> {
+    HashMap::from([
+        ("FIXME".to_string(), Severity::High),
+        ("HACK".to_string(), Severity::High),
+        ("XXX".to_string(), Severity::High),
+        ("TODO".to_string(), Severity::Medium),
+        ("NOTE".to_string(), Severity::Info),
+    ])
+}
+
+/// `[severity]` entries override the built-in ones of the same name (ignoring
+/// case) and keep the rest
+fn merge_default_severity<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<String, Severity>, D::Error> {
+    let custom = HashMap::<String, Severity>::deserialize(deserializer)?;
+    let mut severity = default_severity();
+    severity.retain(|name, _| !custom.keys().any(|key| key.eq_ignore_ascii_case(name)));
+    severity.extend(custom);
+    Ok(severity)
+}
+
+fn default_severity_weights() -> HashMap<Severity, f64> {
+    HashMap::from([
+        (Severity::Critical, 8.0),
+        (Severity::High, 4.0),
+        (Severity::Medium, 2.0),
+        (Severity::Low, 1.0),
+        (Severity::Info, 0.5),
+    ])
+}
+
+fn default_age_scale() -> String {
+    "1y".to_string()
+}
+
+fn default_churn_window() -> String {
+    "90d".to_string()
+}
+
+fn code_marker(
+    name: &str,
+    pattern: &str,
+    scope: CodeMarkerScope,
+    exts: &[&str],
+) -> CodeMarkerConfig {
+    CodeMarkerConfig {
+        name: name.to_string(),
+        pattern: pattern.to_string(),
+        scope,
+        extensions: exts.iter().map(|e| e.to_string()).collect(),
+    }
+}
+
+fn default_code_markers() -> Vec<CodeMarkerConfig> {
+    const JS: &[&str] = &["js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts"];
+    const PY: &[&str] = &["py", "pyi"];
+
+    vec![
+        code_marker(
+            "todo!()",
+            r#"\btodo!\s*\(\s*(?:"((?:[^"\\]|\\.)*)")?"#,
+            CodeMarkerScope::Code,
+            &["rs"],
+        ),
+        code_marker(
+            "unimplemented!()",
+            r#"\bunimplemented!\s*\(\s*(?:"((?:[^"\\]|\\.)*)")?"#,
+            CodeMarkerScope::Code,
+            &["rs"],
+        ),
+        code_marker(
+            "#[allow]",
+            r"#!?\[allow\(([^)]*)\)\]",
+            CodeMarkerScope::Code,
+            &["rs"],
+        ),
+        code_marker("@ts-ignore", r"@ts-ignore\b", CodeMarkerScope::Comment, JS),
+        code_marker(
+            "eslint-disable",
+            r"\beslint-disable(?:-next-line|-line)?\b",
+            CodeMarkerScope::Comment,
+            JS,
+        ),
+        code_marker(
+            "type: ignore",
+            r"\btype:\s*ignore\b(?:\[([^\]]*)\])?",
+            CodeMarkerScope::Comment,
+            PY,
+        ),
+        code_marker(
+            "noqa",
+            r"(?i)\bnoqa\b(?::\s*([A-Z0-9, ]+))?",
+            CodeMarkerScope::Comment,
+            PY,
+        ),
+        code_marker(
+            "pytest.mark.skip",
+            r"\bpytest\.mark\.skip(?:if)?\b(?:\((.*)\))?",
+            CodeMarkerScope::Code,
+            PY,
+        ),
+        code_marker(
+            "@Deprecated",
+            r"@Deprecated\b(?:\((.*)\))?",
+            CodeMarkerScope::Code,
+            &["java", "kt", "kts", "groovy", "scala"],
+        ),
+    ]
+}
+
 fn default_ignored_dirs() -> Vec<String> {
     vec![
         ".git".to_string(),
@@ -187,6 +988,7 @@ fn default_ignored_dirs() -> Vec<String> {
         "__pycache__".to_string(),
         ".pytest_cache".to_string(),
         "coverage".to_string(),
+        ".fossil".to_string(),
     ]
 }
 
@@ -194,6 +996,14 @@ fn default_context_lines() -> usize {
     2
 }
 
+fn default_track_copies() -> bool {
+    true
+}
+
+fn default_blame_cache() -> bool {
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -206,6 +1016,9 @@ mod tests {
             commit_hash: "abc123".to_string(),
             commit_time: Utc::now(),
             age_days: 15,
+            introduced_by: None,
+            introduced_at: None,
+            introduced_commit: None,
         };
         assert_eq!(info.age_display(), "15d");
 
@@ -229,29 +1042,44 @@ mod tests {
         assert!(config.markers.contains(&"FIXME".to_string()));
         assert_eq!(config.context_lines, 2);
         assert!(config.ignored_dirs.contains(&".git".to_string()));
+        assert!(config.code_markers.iter().any(|m| m.name == "todo!()"));
+    }
+
+    #[test]
+    fn test_severity() {
+        assert!(Severity::Critical > Severity::High);
+        assert!(Severity::Low > Severity::Info);
+        assert_eq!("HIGH".parse::<Severity>(), Ok(Severity::High));
+        assert!("urgent".parse::<Severity>().is_err());
+        assert_eq!(
+            serde_json::to_string(&Severity::Critical).unwrap(),
+            "\"critical\""
+        );
+
+        let config = Config::default();
+        assert_eq!(config.severity_of("FIXME"), Severity::High);
+        assert_eq!(config.severity_of("note"), Severity::Info);
+        assert_eq!(config.severity_of("todo!()"), Severity::Medium);
+    }
+
+    #[test]
+    fn test_sort_and_group_keys() {
+        assert_eq!("Score".parse::<SortKey>(), Ok(SortKey::Score));
+        assert_eq!("desc".parse::<SortOrder>(), Ok(SortOrder::Desc));
+        assert_eq!(" dir ".parse::<GroupKey>(), Ok(GroupKey::Dir));
+        assert_eq!(
+            "size".parse::<SortKey>().unwrap_err(),
+            "unknown sort key `size` (expected age, file, author, type, score)"
+        );
+        assert_eq!(SortKey::Age.default_order(), SortOrder::Desc);
+        assert_eq!(SortKey::Author.default_order(), SortOrder::Asc);
     }
 
     #[test]
     fn test_debt_report_creation() {
         let markers = vec![
-            DebtMarker {
-                marker_type: "TODO".to_string(),
-                file_path: PathBuf::from("test.rs"),
-                line_number: 1,
-                line_content: "// TODO: test".to_string(),
-                context_before: vec![],
-                context_after: vec![],
-                git_info: None,
-            },
-            DebtMarker {
-                marker_type: "TODO".to_string(),
-                file_path: PathBuf::from("test.rs"),
-                line_number: 2,
-                line_content: "// TODO: test2".to_string(),
-                context_before: vec![],
-                context_after: vec![],
-                git_info: None,
-            },
+            DebtMarker::test("test.rs", 1, "TODO"),
+            DebtMarker::test("test.rs", 2, "TODO"),
         ];
 
         let report = DebtReport::new(markers, PathBuf::from("."));
@@ -259,4 +1087,110 @@ mod tests {
         assert_eq!(*report.by_type.get("TODO").unwrap(), 2);
         assert_eq!(*report.by_file.get(&PathBuf::from("test.rs")).unwrap(), 2);
     }
+
+    fn marker(
+        file: &str,
+        line: usize,
+        marker_type: &str,
+        author: Option<(&str, i64)>,
+    ) -> DebtMarker {
+        DebtMarker {
+            git_info: author.map(|(author, age_days)| GitBlameInfo::test(author, age_days)),
+            score: line as f64,
+            ..DebtMarker::test(file, line, marker_type)
+        }
+    }
+
+    #[test]
+    fn test_sort_markers() {
+        let mut report = DebtReport::new(
+            vec![
+                marker("src/b.rs", 3, "TODO", Some(("bob", 10))),
+                marker("src/a.rs", 9, "FIXME", None),
+                marker("src/a.rs", 2, "TODO", Some(("alice", 10))),
+                marker("lib.rs", 5, "HACK", Some(("alice", 400))),
+            ],
+            PathBuf::from("."),
+        );
+        let locations = |markers: Vec<&DebtMarker>| -> Vec<String> {
+            markers
+                .iter()
+                .map(|m| format!("{}:{}", m.file_path.display(), m.line_number))
+                .collect()
+        };
+
+        // Ties are broken by location; unblamed markers are left out
+        assert_eq!(
+            locations(report.sorted_markers(SortKey::Age, SortOrder::Desc)),
+            ["lib.rs:5", "src/a.rs:2", "src/b.rs:3"]
+        );
+        assert_eq!(
+            locations(report.sorted_markers(SortKey::Age, SortOrder::Asc)),
+            ["src/a.rs:2", "src/b.rs:3", "lib.rs:5"]
+        );
+        assert_eq!(
+            locations(report.sorted_markers(SortKey::Author, SortOrder::Desc)),
+            ["src/b.rs:3", "lib.rs:5", "src/a.rs:2"]
+        );
+        assert_eq!(
+            locations(report.sorted_markers(SortKey::File, SortOrder::Asc)),
+            ["lib.rs:5", "src/a.rs:2", "src/a.rs:9", "src/b.rs:3"]
+        );
+        assert_eq!(
+            locations(report.sorted_markers(SortKey::Type, SortOrder::Desc)),
+            ["src/a.rs:2", "src/b.rs:3", "lib.rs:5", "src/a.rs:9"]
+        );
+        assert_eq!(
+            locations(report.highest_scoring(2)),
+            ["src/a.rs:9", "lib.rs:5"]
+        );
+
+        // Sorting in place keeps unblamed markers, last in either direction
+        report.sort(SortKey::Age, SortOrder::Asc);
+        assert_eq!(
+            locations(report.markers.iter().collect()),
+            ["src/a.rs:2", "src/b.rs:3", "lib.rs:5", "src/a.rs:9"]
+        );
+    }
+
+    #[test]
+    fn test_group_by() {
+        let report = DebtReport::new(
+            vec![
+                marker("src/b.rs", 3, "TODO", Some(("bob", 10))),
+                marker("src/a.rs", 9, "FIXME", None),
+                marker("src/a.rs", 2, "TODO", Some(("alice", 10))),
+                marker("lib.rs", 5, "HACK", Some(("alice", 400))),
+            ],
+            PathBuf::from("."),
+        );
+
+        let group = |key: &str, count, score, groups| MarkerGroup {
+            key: key.to_string(),
+            count,
+            score,
+            groups,
+        };
+        assert_eq!(
+            report.group_by(&[GroupKey::Dir, GroupKey::Type]),
+            [
+                group(
+                    "src",
+                    3,
+                    14.0,
+                    vec![
+                        group("TODO", 2, 5.0, vec![]),
+                        group("FIXME", 1, 9.0, vec![])
+                    ]
+                ),
+                group(".", 1, 5.0, vec![group("HACK", 1, 5.0, vec![])]),
+            ]
+        );
+
+        let authors = report.group_by(&[GroupKey::Author]);
+        let authors: Vec<(&str, usize)> =
+            authors.iter().map(|g| (g.key.as_str(), g.count)).collect();
+        assert_eq!(authors, [("alice", 2), ("-", 1), ("bob", 1)]);
+        assert!(report.group_by(&[]).is_empty());
+    }
 }
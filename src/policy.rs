@@ -0,0 +1,493 @@
+use crate::cli::PolicyFormat;
+use crate::filters;
+use crate::fingerprint;
+use crate::models::{Config, DebtMarker, DebtReport};
+use anyhow::{Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// A broken policy rule
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Violation {
+    /// Policy setting that was violated, e.g. `max_age`
+    pub rule: String,
+
+    /// Human-readable explanation
+    pub message: String,
+
+    /// File of the offending marker, relative to the repository root
+    /// (absent for rules about the whole report)
+    pub file_path: Option<PathBuf>,
+
+    /// Line of the offending marker
+    pub line_number: Option<usize>,
+
+    /// Type of the offending marker
+    pub marker_type: Option<String>,
+}
+
+impl Violation {
+    fn report(rule: &str, message: String) -> Self {
+        Self {
+            rule: rule.to_string(),
+            message,
+            file_path: None,
+            line_number: None,
+            marker_type: None,
+        }
+    }
+}
+
+/// A `[[policy.forbidden]]` rule with its globs compiled
+struct CompiledForbidden {
+    types: Vec<String>,
+    paths: Vec<String>,
+    globs: GlobSet,
+}
+
+fn compile_forbidden(config: &Config) -> Result<Vec<CompiledForbidden>> {
+    config
+        .policy
+        .forbidden
+        .iter()
+        .map(|rule| {
+            let mut builder = GlobSetBuilder::new();
+            for pattern in &rule.paths {
+                let glob = Glob::new(pattern)
+                    .with_context(|| format!("Invalid forbidden path pattern: {}", pattern))?;
+                builder.add(glob);
+            }
+            Ok(CompiledForbidden {
+                types: rule.types.iter().map(|t| t.to_lowercase()).collect(),
+                paths: rule.paths.clone(),
+                globs: builder.build()?,
+            })
+        })
+        .collect()
+}
+
+/// Check a report against the `[policy]` rules in `config`
+///
+/// `prefix` is the location of the scanned path relative to the repository
+/// root, used to match `forbidden` paths and to report file locations. Age
+/// limits are skipped for markers without git information.
+pub fn evaluate(report: &DebtReport, config: &Config, prefix: &Path) -> Result<Vec<Violation>> {
+    let mut violations = evaluate_totals(report, config);
+    violations.extend(evaluate_markers(
+        &report.markers,
+        &report.scan_path,
+        config,
+        prefix,
+    )?);
+    Ok(violations)
+}
+
+/// Check the rules about a report's totals (`max_total`, `max_debt_index`
+/// and `max_per_type`)
+///
+/// Only the counts are used, so this also works for reports built with
+/// [`DebtReport::count`].
+pub fn evaluate_totals(report: &DebtReport, config: &Config) -> Vec<Violation> {
+    let policy = &config.policy;
+    let mut violations = Vec::new();
+
+    if let Some(max_total) = policy.max_total
+        && report.total_count > max_total
+    {
+        violations.push(Violation::report(
+            "max_total",
+            format!(
+                "{} markers exceed the limit of {}",
+                report.total_count, max_total
+            ),
+        ));
+    }
+
+    if let Some(max_index) = policy.max_debt_index
+        && report.debt_index > max_index
+    {
+        violations.push(Violation::report(
+            "max_debt_index",
+            format!(
+                "Debt index {:.1} exceeds the limit of {}",
+                report.debt_index, max_index
+            ),
+        ));
+    }
+
+    let mut type_limits: Vec<(&String, &usize)> = policy.max_per_type.iter().collect();
+    type_limits.sort();
+    for (marker_type, &max) in type_limits {
+        let count: usize = report
+            .by_type
+            .iter()
+            .filter(|(t, _)| t.eq_ignore_ascii_case(marker_type))
+            .map(|(_, count)| count)
+            .sum();
+        if count > max {
+            violations.push(Violation::report(
+                "max_per_type",
+                format!(
+                    "{} {} markers exceed the limit of {}",
+                    count, marker_type, max
+                ),
+            ));
+        }
+    }
+
+    violations
+}
+
+/// Check the rules about individual markers found under `scan_path`
+/// (`max_age`, `forbidden` and `require_assignee`), in file and line order
+pub fn evaluate_markers(
+    markers: &[DebtMarker],
+    scan_path: &Path,
+    config: &Config,
+    prefix: &Path,
+) -> Result<Vec<Violation>> {
+    let policy = &config.policy;
+    let mut violations = Vec::new();
+
+    let mut age_limits = Vec::new();
+    for (severity, limit) in &policy.max_age {
+        let days = filters::parse_duration(limit)
+            .with_context(|| format!("Invalid max_age for {}: {}", severity, limit))?
+            .as_secs()
+            / (24 * 60 * 60);
+        age_limits.push((*severity, limit, days as i64));
+    }
+
+    let forbidden = compile_forbidden(config)?;
+
+    let mut markers: Vec<_> = markers.iter().collect();
+    markers.sort_by(|a, b| {
+        a.file_path
+            .cmp(&b.file_path)
+            .then(a.line_number.cmp(&b.line_number))
+    });
+
+    for marker in markers {
+        let path = fingerprint::repo_path(marker, scan_path, prefix);
+        let violation = |rule: &str, message: String| Violation {
+            rule: rule.to_string(),
+            message,
+            file_path: Some(path.clone()),
+            line_number: Some(marker.line_number),
+            marker_type: Some(marker.marker_type.clone()),
+        };
+
+        if let Some(git_info) = &marker.git_info {
+            for (severity, limit, days) in &age_limits {
+                if marker.severity == *severity && git_info.age_days > *days {
+                    violations.push(violation(
+                        "max_age",
+                        format!(
+                            "{} is {} days old; {} severity markers must be under {}",
+                            marker.marker_type, git_info.age_days, severity, limit
+                        ),
+                    ));
+                }
+            }
+        }
+
+        let marker_type = marker.marker_type.to_lowercase();
+        for rule in &forbidden {
+            if rule.types.contains(&marker_type) && rule.globs.is_match(&path) {
+                violations.push(violation(
+                    "forbidden",
+                    format!(
+                        "{} markers are not allowed in {}",
+                        marker.marker_type,
+                        rule.paths.join(", ")
+                    ),
+                ));
+            }
+        }
+
+        if policy.require_assignee && marker.assignee.is_none() {
+            violations.push(violation(
+                "require_assignee",
+                format!("{} has no assignee", marker.marker_type),
+            ));
+        }
+    }
+
+    Ok(violations)
+}
+
+/// Escape data for a GitHub Actions workflow command
+fn escape_github(text: &str, property: bool) -> String {
+    let escaped = text
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A");
+    if property {
+        escaped.replace(':', "%3A").replace(',', "%2C")
+    } else {
+        escaped
+    }
+}
+
+/// Render violations for CI
+///
+/// `text` gives one `path:line: rule: message` line per violation, `github`
+/// gives workflow commands that annotate the build, and `json` an object with
+/// a `violations` array.
+pub fn format_violations(violations: &[Violation], format: PolicyFormat) -> Result<String> {
+    let output = match format {
+        PolicyFormat::Text => violations
+            .iter()
+            .map(|v| match (&v.file_path, v.line_number) {
+                (Some(path), Some(line)) => {
+                    format!("{}:{}: {}: {}", path.display(), line, v.rule, v.message)
+                }
+                _ => format!("{}: {}", v.rule, v.message),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        PolicyFormat::Github => violations
+            .iter()
+            .map(|v| {
+                let mut properties = Vec::new();
+                if let Some(path) = &v.file_path {
+                    properties.push(format!(
+                        "file={}",
+                        escape_github(&path.display().to_string(), true)
+                    ));
+                }
+                if let Some(line) = v.line_number {
+                    properties.push(format!("line={}", line));
+                }
+                properties.push(format!(
+                    "title={}",
+                    escape_github(&format!("fossil {}", v.rule), true)
+                ));
+                format!(
+                    "::error {}::{}",
+                    properties.join(","),
+                    escape_github(&v.message, false)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        PolicyFormat::Json => serde_json::to_string_pretty(&serde_json::json!({
+            "violations": violations,
+        }))
+        .context("Failed to serialize policy violations")?,
+    };
+
+    Ok(output)
+}
+
+/// Writes violations as they're found, keeping only a count, so streamed
+/// scans don't hold every violation in memory
+///
+/// The output matches [`format_violations`] on all of the violations at once,
+/// followed by a newline; nothing is written if there are none.
+pub struct ViolationWriter<W: Write> {
+    out: W,
+    format: PolicyFormat,
+    count: usize,
+}
+
+impl<W: Write> ViolationWriter<W> {
+    pub fn new(out: W, format: PolicyFormat) -> Self {
+        Self {
+            out,
+            format,
+            count: 0,
+        }
+    }
+
+    /// Write a batch of violations
+    pub fn write(&mut self, violations: &[Violation]) -> Result<()> {
+        if violations.is_empty() {
+            return Ok(());
+        }
+
+        let mut output = String::new();
+        match self.format {
+            PolicyFormat::Text | PolicyFormat::Github => {
+                output.push_str(&format_violations(violations, self.format)?);
+                output.push('\n');
+            }
+            // The array is opened by the first violation and closed by `finish`
+            PolicyFormat::Json => {
+                for (idx, violation) in violations.iter().enumerate() {
+                    output.push_str(if self.count + idx == 0 {
+                        "{\n  \"violations\": [\n"
+                    } else {
+                        ",\n"
+                    });
+                    // Through `Value`, for the same key order as `format_violations`
+                    let json = serde_json::to_value(violation)
+                        .and_then(|value| serde_json::to_string_pretty(&value))
+                        .context("Failed to serialize policy violations")?;
+                    let lines: Vec<String> =
+                        json.lines().map(|line| format!("    {}", line)).collect();
+                    output.push_str(&lines.join("\n"));
+                }
+            }
+        }
+
+        self.out
+            .write_all(output.as_bytes())
+            .context("Failed to write policy violations")?;
+        self.count += violations.len();
+        Ok(())
+    }
+
+    /// Finish the output, returning the number of violations written
+    pub fn finish(mut self) -> Result<usize> {
+        if matches!(self.format, PolicyFormat::Json) && self.count > 0 {
+            self.out
+                .write_all(b"\n  ]\n}\n")
+                .context("Failed to write policy violations")?;
+        }
+        self.out
+            .flush()
+            .context("Failed to write policy violations")?;
+        Ok(self.count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ForbiddenRule, GitBlameInfo, Severity};
+    use std::collections::HashMap;
+
+    fn marker(file: &str, line: usize, marker_type: &str, age_days: i64) -> DebtMarker {
+        DebtMarker {
+            severity: Config::default().severity_of(marker_type),
+            git_info: Some(GitBlameInfo::test("Alice", age_days)),
+            ..DebtMarker::test(file, line, marker_type)
+        }
+    }
+
+    fn report() -> DebtReport {
+        let mut assigned = marker("./src/lib.rs", 3, "TODO", 10);
+        assigned.assignee = Some("alice".to_string());
+        DebtReport::new(
+            vec![
+                assigned,
+                marker("./src/core/engine.rs", 7, "HACK", 5),
+                marker("./src/lib.rs", 9, "FIXME", 120),
+                marker("./src/lib.rs", 12, "TODO", 120),
+            ],
+            PathBuf::from("."),
+        )
+    }
+
+    #[test]
+    fn test_empty_policy_passes() {
+        let violations = evaluate(&report(), &Config::default(), Path::new("")).unwrap();
+        assert!(violations.is_empty());
+    }
+
+    #[test]
+    fn test_evaluate_rules() {
+        let mut config = Config::default();
+        config.policy.max_total = Some(2);
+        config.policy.max_debt_index = Some(100.0);
+        config.policy.max_per_type = HashMap::from([("hack".to_string(), 0)]);
+        config.policy.max_age = HashMap::from([(Severity::High, "90d".to_string())]);
+        config.policy.forbidden = vec![ForbiddenRule {
+            types: vec!["HACK".to_string()],
+            paths: vec!["src/core/**".to_string()],
+        }];
+        config.policy.require_assignee = true;
+
+        let violations = evaluate(&report(), &config, Path::new("")).unwrap();
+        let rules: Vec<(&str, Option<usize>)> = violations
+            .iter()
+            .map(|v| (v.rule.as_str(), v.line_number))
+            .collect();
+        assert_eq!(
+            rules,
+            vec![
+                ("max_total", None),
+                ("max_per_type", None),
+                ("forbidden", Some(7)),
+                ("require_assignee", Some(7)),
+                ("max_age", Some(9)),
+                ("require_assignee", Some(9)),
+                ("require_assignee", Some(12)), // Medium has no age limit
+            ]
+        );
+        assert_eq!(
+            violations[2].file_path,
+            Some(PathBuf::from("src/core/engine.rs"))
+        );
+
+        let mut scored = report();
+        scored.debt_index = 120.0;
+        let violations = evaluate(&scored, &config, Path::new("")).unwrap();
+        assert_eq!(violations[1].rule, "max_debt_index");
+
+        // Paths are matched relative to the repository root
+        let violations = evaluate(&report(), &config, Path::new("sub")).unwrap();
+        assert!(violations.iter().all(|v| v.rule != "forbidden"));
+    }
+
+    #[test]
+    fn test_format_violations() {
+        let violations = vec![
+            Violation::report("max_total", "3 markers exceed the limit of 2".to_string()),
+            Violation {
+                rule: "forbidden".to_string(),
+                message: "HACK markers are not allowed in src/core/**".to_string(),
+                file_path: Some(PathBuf::from("src/core/a,b.rs")),
+                line_number: Some(7),
+                marker_type: Some("HACK".to_string()),
+            },
+        ];
+
+        let text = format_violations(&violations, PolicyFormat::Text).unwrap();
+        assert_eq!(
+            text,
+            "max_total: 3 markers exceed the limit of 2\n\
+             src/core/a,b.rs:7: forbidden: HACK markers are not allowed in src/core/**"
+        );
+
+        let github = format_violations(&violations, PolicyFormat::Github).unwrap();
+        assert!(github.starts_with("::error title=fossil max_total::3 markers"));
+        assert!(github.contains("::error file=src/core/a%2Cb.rs,line=7,title=fossil forbidden::"));
+
+        let json = format_violations(&violations, PolicyFormat::Json).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["violations"][1]["line_number"], 7);
+        assert!(parsed["violations"][0]["file_path"].is_null());
+    }
+
+    #[test]
+    fn test_violation_writer() {
+        let violations = vec![
+            Violation::report("max_total", "3 markers exceed the limit of 2".to_string()),
+            Violation::report("max_per_type", "2 TODO markers".to_string()),
+            Violation::report("max_debt_index", "debt index too high".to_string()),
+        ];
+
+        // Writing in batches gives the same output as formatting at once
+        for format in [PolicyFormat::Text, PolicyFormat::Github, PolicyFormat::Json] {
+            let mut out = Vec::new();
+            let mut writer = ViolationWriter::new(&mut out, format);
+            writer.write(&violations[..1]).unwrap();
+            writer.write(&[]).unwrap();
+            writer.write(&violations[1..]).unwrap();
+            assert_eq!(writer.finish().unwrap(), 3);
+            assert_eq!(
+                String::from_utf8(out).unwrap(),
+                format!("{}\n", format_violations(&violations, format).unwrap())
+            );
+
+            let mut out = Vec::new();
+            let writer = ViolationWriter::new(&mut out, format);
+            assert_eq!(writer.finish().unwrap(), 0);
+            assert!(out.is_empty());
+        }
+    }
+}
@@ -1,33 +1,164 @@
-use crate::cli::OutputFormat;
-use crate::models::DebtReport;
-use anyhow::{Context, Result};
+use crate::cli::{Column, HotspotFormat, OutputFormat};
+use crate::diff::MarkerDiff;
+use crate::fingerprint;
+use crate::hotspots::Hotspot;
+use crate::html;
+use crate::models::{DebtMarker, DebtReport, GroupKey, MarkerGroup, Severity, SortKey, SortOrder};
+use crate::policy::Violation;
+use crate::template;
+use anyhow::{Context, Result, bail};
 use comfy_table::{Cell, Color, ContentArrangement, Table, presets::UTF8_FULL};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 
 /// Format report as count only
 fn format_count_only(report: &DebtReport) -> String {
     report.total_count.to_string()
 }
 
+/// How `generate_report` presents a report
+pub struct ReportOptions<'a> {
+    /// Length of the top markers list
+    pub top_n: usize,
+
+    /// Order of the top markers list
+    pub sort_by: SortKey,
+
+    /// Direction of the top markers list
+    pub sort_order: SortOrder,
+
+    /// Keys to summarize markers by, replacing the usual summaries in
+    /// terminal, markdown and template output and added to JSON output
+    pub group_by: &'a [GroupKey],
+
+    /// Print only the number of markers
+    pub count_only: bool,
+
+    /// Template to render instead of the format (see
+    /// [`template::format_template`])
+    pub template: Option<&'a str>,
+
+    /// Columns for CSV and TSV output
+    pub columns: &'a [Column],
+
+    /// Markers below this severity are skipped, rather than failed, in JUnit
+    /// output
+    pub junit_fail_on: Severity,
+
+    /// Policy violations, reported as failed test cases in JUnit output
+    pub violations: &'a [Violation],
+
+    /// Location of the scanned path within the repository, for output that
+    /// needs repository-relative paths
+    pub prefix: &'a Path,
+}
+
 /// Generate and output a report in the specified format
 pub fn generate_report(
     report: &DebtReport,
     format: OutputFormat,
     output_path: Option<&Path>,
-    top_n: usize,
-    count_only: bool,
+    options: &ReportOptions,
 ) -> Result<()> {
-    let output = if count_only {
+    let output = if options.count_only {
         format_count_only(report)
+    } else if let Some(template) = options.template {
+        template::format_template(
+            report,
+            template,
+            options.top_n,
+            options.sort_by,
+            options.sort_order,
+            options.group_by,
+        )?
     } else {
+        let groupable = matches!(
+            format,
+            OutputFormat::Terminal | OutputFormat::Markdown | OutputFormat::Json
+        );
+        if !options.group_by.is_empty() && !groupable {
+            bail!(
+                "--group-by is only supported for terminal, markdown and JSON output and templates"
+            );
+        }
+
         match format {
-            OutputFormat::Terminal => format_terminal(report, top_n),
-            OutputFormat::Markdown => format_markdown(report, top_n),
-            OutputFormat::Json => format_json(report)?,
+            OutputFormat::Terminal => format_terminal(
+                report,
+                options.top_n,
+                options.sort_by,
+                options.sort_order,
+                options.group_by,
+            ),
+            OutputFormat::Markdown => format_markdown(
+                report,
+                options.top_n,
+                options.sort_by,
+                options.sort_order,
+                options.group_by,
+            ),
+            OutputFormat::Json => format_json(report, options.group_by)?,
+            OutputFormat::Sarif => format_sarif(report, options.prefix)?,
+            OutputFormat::Csv => format_delimited(report, options.columns, ','),
+            OutputFormat::Tsv => format_delimited(report, options.columns, '\t'),
+            OutputFormat::Html => html::format_html(report)?,
+            OutputFormat::Junit => format_junit(report, options.violations, options.junit_fail_on),
+            OutputFormat::Codeclimate => format_codeclimate(report, options.prefix)?,
+            OutputFormat::Ndjson => format_ndjson(report)?,
         }
     };
 
+    write_output(&output, output_path)
+}
+
+/// Generate and output a diff between two revisions in the specified format
+pub fn generate_diff_report(
+    diff: &MarkerDiff,
+    format: OutputFormat,
+    output_path: Option<&Path>,
+    columns: &[Column],
+) -> Result<()> {
+    let output = match format {
+        OutputFormat::Terminal => format_diff_terminal(diff),
+        OutputFormat::Markdown => format_diff_markdown(diff),
+        OutputFormat::Json => format_diff_json(diff)?,
+        OutputFormat::Sarif => format_diff_sarif(diff)?,
+        OutputFormat::Csv => format_diff_delimited(diff, columns, ','),
+        OutputFormat::Tsv => format_diff_delimited(diff, columns, '\t'),
+        OutputFormat::Html => bail!("HTML output is not supported for diffs"),
+        OutputFormat::Junit => bail!("JUnit output is not supported for diffs"),
+        OutputFormat::Codeclimate => format_diff_codeclimate(diff)?,
+        OutputFormat::Ndjson => bail!("NDJSON output is not supported for diffs"),
+    };
+
+    write_output(&output, output_path)
+}
+
+/// Generate and output ranked hotspots in the specified format
+///
+/// `since` is the churn window as given on the command line, e.g. `90d`.
+pub fn generate_hotspot_report(
+    hotspots: &[Hotspot],
+    since: &str,
+    format: HotspotFormat,
+    output_path: Option<&Path>,
+) -> Result<()> {
+    let output = match format {
+        HotspotFormat::Terminal => format_hotspots_terminal(hotspots, since),
+        HotspotFormat::Json => serde_json::to_string_pretty(&serde_json::json!({
+            "since": since,
+            "hotspots": hotspots,
+        }))
+        .context("Failed to serialize hotspots to JSON")?,
+    };
+
+    write_output(&output, output_path)
+}
+
+/// Write a formatted report to a file, or stdout if no path is given
+fn write_output(output: &str, output_path: Option<&Path>) -> Result<()> {
     if let Some(path) = output_path {
         fs::write(path, output)
             .with_context(|| format!("Failed to write output to {}", path.display()))?;
@@ -39,8 +170,213 @@ pub fn generate_report(
     Ok(())
 }
 
+/// Counts sorted highest first, ties by name
+pub fn sorted_counts<K: Ord>(counts: &HashMap<K, usize>) -> Vec<(&K, usize)> {
+    let mut counts: Vec<(&K, usize)> = counts.iter().map(|(k, &c)| (k, c)).collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    counts
+}
+
+/// Non-zero severity counts, most severe first
+pub fn severity_counts(report: &DebtReport) -> Vec<(Severity, usize)> {
+    Severity::ALL
+        .into_iter()
+        .filter_map(|severity| {
+            report
+                .by_severity
+                .get(&severity)
+                .map(|&count| (severity, count))
+        })
+        .collect()
+}
+
+/// The top markers list: its title and markers
+pub fn top_markers(
+    report: &DebtReport,
+    top_n: usize,
+    sort_by: SortKey,
+    sort_order: SortOrder,
+) -> (String, Vec<&DebtMarker>) {
+    let mut markers = report.sorted_markers(sort_by, sort_order);
+    markers.truncate(top_n);
+
+    let count = markers.len();
+    let title = match (sort_by, sort_order) {
+        (SortKey::Age, SortOrder::Desc) => format!("Top {} Oldest Markers", count),
+        (SortKey::Age, SortOrder::Asc) => format!("Top {} Newest Markers", count),
+        (SortKey::Score, SortOrder::Desc) => format!("Top {} Highest-Scoring Markers", count),
+        (SortKey::Score, SortOrder::Asc) => format!("Top {} Lowest-Scoring Markers", count),
+        (key, SortOrder::Asc) => format!("Top {} Markers by {}", count, key.label()),
+        (key, SortOrder::Desc) => {
+            format!("Top {} Markers by {} (Descending)", count, key.label())
+        }
+    };
+    (title, markers)
+}
+
+/// Groups from [`DebtReport::group_by`] in display order, each with its
+/// nesting depth
+pub fn flatten_groups(groups: &[MarkerGroup]) -> Vec<(usize, &MarkerGroup)> {
+    fn visit<'a>(
+        groups: &'a [MarkerGroup],
+        depth: usize,
+        rows: &mut Vec<(usize, &'a MarkerGroup)>,
+    ) {
+        for group in groups {
+            rows.push((depth, group));
+            visit(&group.groups, depth + 1, rows);
+        }
+    }
+
+    let mut rows = Vec::new();
+    visit(groups, 0, &mut rows);
+    rows
+}
+
+/// Heading for a grouped summary, e.g. `Directory, Type`
+fn group_heading(group_by: &[GroupKey]) -> String {
+    let labels: Vec<&str> = group_by.iter().map(|key| key.label()).collect();
+    labels.join(", ")
+}
+
+/// Highest score totals, at most `limit`
+pub fn top_scores(scores: &HashMap<PathBuf, f64>, limit: usize) -> Vec<(&PathBuf, f64)> {
+    let mut scores: Vec<(&PathBuf, f64)> = scores.iter().map(|(p, &s)| (p, s)).collect();
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(b.0)));
+    scores.truncate(limit);
+    scores
+}
+
+/// Terminal color for a severity
+fn severity_color(severity: Severity) -> Color {
+    match severity {
+        Severity::Critical => Color::Red,
+        Severity::High => Color::Yellow,
+        Severity::Medium => Color::Reset,
+        Severity::Low | Severity::Info => Color::DarkGrey,
+    }
+}
+
+/// A table in the terminal report's style
+///
+/// Cells in `severity_column` that name a severity are colored by it.
+pub fn terminal_table(
+    header: &[&str],
+    rows: Vec<Vec<String>>,
+    severity_column: Option<usize>,
+) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(header.iter().map(|h| Cell::new(h).fg(Color::Cyan)));
+
+    for row in rows {
+        table.add_row(row.into_iter().enumerate().map(
+            |(idx, text)| match text.parse::<Severity>() {
+                Ok(severity) if severity_column == Some(idx) => {
+                    Cell::new(text).fg(severity_color(severity))
+                }
+                _ => Cell::new(text),
+            },
+        ));
+    }
+
+    table
+}
+
+/// The terminal report's summaries by type, severity, author and score
+fn terminal_summary(report: &DebtReport) -> String {
+    let mut output = String::new();
+
+    // Summary by type
+    if !report.by_type.is_empty() {
+        output.push_str("Summary by Type:\n");
+        let rows = sorted_counts(&report.by_type)
+            .into_iter()
+            .map(|(marker_type, count)| vec![marker_type.clone(), count.to_string()])
+            .collect();
+        let type_table = terminal_table(&["Type", "Count"], rows, None);
+        output.push_str(&format!("{}\n\n", type_table));
+    }
+
+    // Summary by severity
+    if !report.by_severity.is_empty() {
+        output.push_str("Summary by Severity:\n");
+        let rows = severity_counts(report)
+            .into_iter()
+            .map(|(severity, count)| vec![severity.to_string(), count.to_string()])
+            .collect();
+        let severity_table = terminal_table(&["Severity", "Count"], rows, Some(0));
+        output.push_str(&format!("{}\n\n", severity_table));
+    }
+
+    // Summary by author
+    if !report.by_author.is_empty() {
+        output.push_str("Summary by Author:\n");
+        let rows = sorted_counts(&report.by_author)
+            .into_iter()
+            .take(10) // Show top 10 authors
+            .map(|(author, count)| vec![author.clone(), count.to_string()])
+            .collect();
+        let author_table = terminal_table(&["Author", "Count"], rows, None);
+        output.push_str(&format!("{}\n\n", author_table));
+    }
+
+    // Score totals by file and directory
+    let score_sections = [
+        ("File", &report.score_by_file),
+        ("Directory", &report.score_by_dir),
+    ];
+    for (label, scores) in score_sections {
+        let top = top_scores(scores, 10);
+        if top.iter().all(|(_, score)| *score == 0.0) {
+            continue;
+        }
+
+        output.push_str(&format!("Debt Score by {}:\n", label));
+        let rows = top
+            .into_iter()
+            .map(|(path, score)| vec![path.display().to_string(), format!("{:.1}", score)])
+            .collect();
+        let score_table = terminal_table(&[label, "Score"], rows, None);
+        output.push_str(&format!("{}\n\n", score_table));
+    }
+
+    output
+}
+
+/// A terminal table of markers grouped by `group_by`, a column per key
+fn terminal_groups(report: &DebtReport, group_by: &[GroupKey]) -> String {
+    let groups = report.group_by(group_by);
+    if groups.is_empty() {
+        return String::new();
+    }
+
+    let mut header: Vec<&str> = group_by.iter().map(|key| key.label()).collect();
+    header.extend(["Count", "Score"]);
+    let rows = flatten_groups(&groups)
+        .into_iter()
+        .map(|(depth, group)| {
+            let mut row = vec![String::new(); group_by.len()];
+            row[depth] = group.key.clone();
+            row.extend([group.count.to_string(), format!("{:.1}", group.score)]);
+            row
+        })
+        .collect();
+    let severity_column = group_by.iter().position(|&key| key == GroupKey::Severity);
+    let table = terminal_table(&header, rows, severity_column);
+    format!("Summary by {}:\n{}\n\n", group_heading(group_by), table)
+}
+
 /// Format report as terminal table
-fn format_terminal(report: &DebtReport, top_n: usize) -> String {
+fn format_terminal(
+    report: &DebtReport,
+    top_n: usize,
+    sort_by: SortKey,
+    sort_order: SortOrder,
+    group_by: &[GroupKey],
+) -> String {
     let mut output = String::new();
 
     // Header
@@ -52,131 +388,218 @@ fn format_terminal(report: &DebtReport, top_n: usize) -> String {
         report.scan_path.display()
     ));
     output.push_str(&format!("│ Total Markers: {:<41} │\n", report.total_count));
+    output.push_str(&format!(
+        "│ Debt Index: {:<44} │\n",
+        format!("{:.1}", report.debt_index)
+    ));
     output.push_str(&format!("╰{}╯\n\n", line));
 
-    // Summary by type
-    if !report.by_type.is_empty() {
-        output.push_str("Summary by Type:\n");
-        let mut type_table = Table::new();
-        type_table
-            .load_preset(UTF8_FULL)
-            .set_content_arrangement(ContentArrangement::Dynamic)
-            .set_header(vec![
-                Cell::new("Type").fg(Color::Cyan),
-                Cell::new("Count").fg(Color::Cyan),
-            ]);
+    if group_by.is_empty() {
+        output.push_str(&terminal_summary(report));
+    } else {
+        output.push_str(&terminal_groups(report, group_by));
+    }
 
-        let mut types: Vec<_> = report.by_type.iter().collect();
-        types.sort_by(|a, b| b.1.cmp(a.1)); // Sort by count descending
+    // Top N markers
+    let (title, top) = top_markers(report, top_n, sort_by, sort_order);
+    if !top.is_empty() {
+        output.push_str(&format!("{}:\n", title));
+        let rows = top
+            .into_iter()
+            .map(|marker| {
+                let (author, age) = match &marker.git_info {
+                    Some(git_info) => (git_info.author.clone(), git_info.age_display()),
+                    None => ("-".to_string(), "-".to_string()),
+                };
+                vec![
+                    marker.marker_type.clone(),
+                    marker.severity.to_string(),
+                    format!("{:.1}", marker.score),
+                    marker.file_path.display().to_string(),
+                    marker.line_number.to_string(),
+                    author,
+                    age,
+                    marker.assignee.clone().unwrap_or_else(|| "-".to_string()),
+                    marker.description.clone(),
+                ]
+            })
+            .collect();
+        let top_table = terminal_table(
+            &[
+                "Type",
+                "Severity",
+                "Score",
+                "File",
+                "Line",
+                "Author",
+                "Age",
+                "Assignee",
+                "Description",
+            ],
+            rows,
+            Some(1),
+        );
+        output.push_str(&format!("{}\n", top_table));
+    }
 
-        for (marker_type, count) in types {
-            type_table.add_row(vec![marker_type.as_str(), &count.to_string()]);
-        }
+    output
+}
 
-        output.push_str(&format!("{}\n\n", type_table));
+/// Format hotspots as a ranked terminal table
+fn format_hotspots_terminal(hotspots: &[Hotspot], since: &str) -> String {
+    let mut output = String::new();
+
+    // Header
+    let line = "─".repeat(58);
+    output.push_str(&format!("╭{}╮\n", line));
+    output.push_str(&format!("│ {:^56} │\n", "Fossil - Debt Hotspots"));
+    output.push_str(&format!(
+        "│ {:<56} │\n",
+        format!("Files with debt changed in the last {}", since)
+    ));
+    output.push_str(&format!("╰{}╯\n\n", line));
+
+    if hotspots.is_empty() {
+        output.push_str("No files with both debt and recent commits.\n");
+        return output;
     }
 
-    // Summary by author
-    if !report.by_author.is_empty() {
-        output.push_str("Summary by Author:\n");
-        let mut author_table = Table::new();
-        author_table
-            .load_preset(UTF8_FULL)
-            .set_content_arrangement(ContentArrangement::Dynamic)
-            .set_header(vec![
-                Cell::new("Author").fg(Color::Cyan),
-                Cell::new("Count").fg(Color::Cyan),
-            ]);
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Rank").fg(Color::Cyan),
+            Cell::new("File").fg(Color::Cyan),
+            Cell::new("Markers").fg(Color::Cyan),
+            Cell::new("Score").fg(Color::Cyan),
+            Cell::new("Commits").fg(Color::Cyan),
+            Cell::new("Authors").fg(Color::Cyan),
+            Cell::new("Hotspot").fg(Color::Cyan),
+        ]);
+
+    for (idx, hotspot) in hotspots.iter().enumerate() {
+        table.add_row(vec![
+            Cell::new(idx + 1),
+            Cell::new(hotspot.file_path.display().to_string()),
+            Cell::new(hotspot.markers),
+            Cell::new(format!("{:.1}", hotspot.score)),
+            Cell::new(hotspot.commits),
+            Cell::new(hotspot.authors),
+            Cell::new(format!("{:.1}", hotspot.hotspot)),
+        ]);
+    }
+
+    output.push_str(&format!("{}\n", table));
+    output.push_str("Hotspot = debt score × commits\n");
+    output
+}
 
-        let mut authors: Vec<_> = report.by_author.iter().collect();
-        authors.sort_by(|a, b| b.1.cmp(a.1)); // Sort by count descending
-        authors.truncate(10); // Show top 10 authors
+/// The markdown report's summaries by type, severity, author and score
+fn markdown_summary(report: &DebtReport) -> String {
+    let mut output = String::new();
 
-        for (author, count) in authors {
-            author_table.add_row(vec![author.as_str(), &count.to_string()]);
+    // Summary by type
+    if !report.by_type.is_empty() {
+        output.push_str("## Summary by Type\n\n");
+        for (marker_type, count) in sorted_counts(&report.by_type) {
+            output.push_str(&format!("- **{}**: {}\n", marker_type, count));
         }
+        output.push('\n');
+    }
 
-        output.push_str(&format!("{}\n\n", author_table));
+    // Summary by severity
+    if !report.by_severity.is_empty() {
+        output.push_str("## Summary by Severity\n\n");
+        for (severity, count) in severity_counts(report) {
+            output.push_str(&format!("- **{}**: {}\n", severity, count));
+        }
+        output.push('\n');
     }
 
-    // Top N oldest markers
-    let oldest = report.oldest_markers(top_n);
-    if !oldest.is_empty() {
-        output.push_str(&format!("Top {} Oldest Markers:\n", oldest.len()));
-        let mut oldest_table = Table::new();
-        oldest_table
-            .load_preset(UTF8_FULL)
-            .set_content_arrangement(ContentArrangement::Dynamic)
-            .set_header(vec![
-                Cell::new("Type").fg(Color::Cyan),
-                Cell::new("File").fg(Color::Cyan),
-                Cell::new("Line").fg(Color::Cyan),
-                Cell::new("Author").fg(Color::Cyan),
-                Cell::new("Age").fg(Color::Cyan),
-            ]);
+    // Summary by author
+    if !report.by_author.is_empty() {
+        output.push_str("## Summary by Author (Top 10)\n\n");
+        for (author, count) in sorted_counts(&report.by_author).into_iter().take(10) {
+            output.push_str(&format!("- **{}**: {}\n", author, count));
+        }
+        output.push('\n');
+    }
 
-        for marker in oldest {
-            let git_info = marker.git_info.as_ref().unwrap();
-            oldest_table.add_row(vec![
-                Cell::new(&marker.marker_type),
-                Cell::new(marker.file_path.display().to_string()),
-                Cell::new(marker.line_number.to_string()),
-                Cell::new(&git_info.author),
-                Cell::new(git_info.age_display()),
-            ]);
+    // Score totals by file and directory
+    let score_sections = [
+        ("File", &report.score_by_file),
+        ("Directory", &report.score_by_dir),
+    ];
+    for (label, scores) in score_sections {
+        let top = top_scores(scores, 10);
+        if top.iter().all(|(_, score)| *score == 0.0) {
+            continue;
         }
 
-        output.push_str(&format!("{}\n", oldest_table));
+        output.push_str(&format!("## Debt Score by {}\n\n", label));
+        for (path, score) in top {
+            output.push_str(&format!("- `{}`: {:.1}\n", path.display(), score));
+        }
+        output.push('\n');
     }
 
     output
 }
 
+/// A nested markdown list of markers grouped by `group_by`
+fn markdown_groups(report: &DebtReport, group_by: &[GroupKey]) -> String {
+    let groups = report.group_by(group_by);
+    if groups.is_empty() {
+        return String::new();
+    }
+
+    let mut output = format!("## Summary by {}\n\n", group_heading(group_by));
+    for (depth, group) in flatten_groups(&groups) {
+        output.push_str(&format!(
+            "{}- **{}**: {} (score {:.1})\n",
+            "  ".repeat(depth),
+            group.key,
+            group.count,
+            group.score
+        ));
+    }
+    output.push('\n');
+    output
+}
+
 /// Format report as Markdown
-fn format_markdown(report: &DebtReport, top_n: usize) -> String {
+fn format_markdown(
+    report: &DebtReport,
+    top_n: usize,
+    sort_by: SortKey,
+    sort_order: SortOrder,
+    group_by: &[GroupKey],
+) -> String {
     let mut output = String::new();
 
     // Header
     output.push_str("# Fossil - Technical Debt Report\n\n");
     output.push_str(&format!("**Scanned**: `{}`\n", report.scan_path.display()));
     output.push_str(&format!("**Total Markers**: {}\n", report.total_count));
+    output.push_str(&format!("**Debt Index**: {:.1}\n", report.debt_index));
     output.push_str(&format!(
         "**Generated**: {}\n\n",
         report.scan_time.format("%Y-%m-%d %H:%M:%S UTC")
     ));
 
-    // Summary by type
-    if !report.by_type.is_empty() {
-        output.push_str("## Summary by Type\n\n");
-        let mut types: Vec<_> = report.by_type.iter().collect();
-        types.sort_by(|a, b| b.1.cmp(a.1));
-
-        for (marker_type, count) in types {
-            output.push_str(&format!("- **{}**: {}\n", marker_type, count));
-        }
-        output.push('\n');
-    }
-
-    // Summary by author
-    if !report.by_author.is_empty() {
-        output.push_str("## Summary by Author (Top 10)\n\n");
-        let mut authors: Vec<_> = report.by_author.iter().collect();
-        authors.sort_by(|a, b| b.1.cmp(a.1));
-        authors.truncate(10);
-
-        for (author, count) in authors {
-            output.push_str(&format!("- **{}**: {}\n", author, count));
-        }
-        output.push('\n');
+    if group_by.is_empty() {
+        output.push_str(&markdown_summary(report));
+    } else {
+        output.push_str(&markdown_groups(report, group_by));
     }
 
-    // Top N oldest markers
-    let oldest = report.oldest_markers(top_n);
-    if !oldest.is_empty() {
-        output.push_str(&format!("## Top {} Oldest Markers\n\n", oldest.len()));
+    // Top N markers
+    let (title, top) = top_markers(report, top_n, sort_by, sort_order);
+    if !top.is_empty() {
+        output.push_str(&format!("## {}\n\n", title));
 
-        for (idx, marker) in oldest.iter().enumerate() {
-            let git_info = marker.git_info.as_ref().unwrap();
+        for (idx, marker) in top.iter().enumerate() {
             output.push_str(&format!(
                 "{}. **{}** in `{}:{}`\n",
                 idx + 1,
@@ -184,14 +607,34 @@ fn format_markdown(report: &DebtReport, top_n: usize) -> String {
                 marker.file_path.display(),
                 marker.line_number
             ));
-            output.push_str(&format!("   - Author: {}\n", git_info.author));
-            output.push_str(&format!(
-                "   - Age: {} ({} days)\n",
-                git_info.age_display(),
-                git_info.age_days
-            ));
-            output.push_str(&format!("   - Commit: {}\n", git_info.commit_hash));
+            output.push_str(&format!("   - Severity: {}\n", marker.severity));
+            output.push_str(&format!("   - Score: {:.1}\n", marker.score));
+            if let Some(git_info) = &marker.git_info {
+                output.push_str(&format!("   - Author: {}\n", git_info.author));
+                output.push_str(&format!(
+                    "   - Age: {} ({} days)\n",
+                    git_info.age_display(),
+                    git_info.age_days
+                ));
+                output.push_str(&format!("   - Commit: {}\n", git_info.commit_hash));
+                if let (Some(by), Some(at)) = (&git_info.introduced_by, &git_info.introduced_at) {
+                    output.push_str(&format!(
+                        "   - Introduced: {} on {}\n",
+                        by,
+                        at.format("%Y-%m-%d")
+                    ));
+                }
+            }
             output.push_str(&format!("   - Line: `{}`\n", marker.line_content.trim()));
+            if !marker.description.is_empty() {
+                output.push_str(&format!("   - Description: {}\n", marker.description));
+            }
+            if let Some(ref assignee) = marker.assignee {
+                output.push_str(&format!("   - Assignee: {}\n", assignee));
+            }
+            if !marker.issue_refs.is_empty() {
+                output.push_str(&format!("   - Issues: {}\n", marker.issue_refs.join(", ")));
+            }
 
             // Add context if available
             if !marker.context_before.is_empty() || !marker.context_after.is_empty() {
@@ -200,6 +643,9 @@ fn format_markdown(report: &DebtReport, top_n: usize) -> String {
                     output.push_str(&format!("{}\n", line));
                 }
                 output.push_str(&format!("{} <-- MARKER\n", marker.line_content));
+                for line in &marker.continuation_lines {
+                    output.push_str(&format!("{}\n", line));
+                }
                 for line in &marker.context_after {
                     output.push_str(&format!("{}\n", line));
                 }
@@ -212,9 +658,705 @@ fn format_markdown(report: &DebtReport, top_n: usize) -> String {
     output
 }
 
-/// Format report as JSON
-fn format_json(report: &DebtReport) -> Result<String> {
-    serde_json::to_string_pretty(report).context("Failed to serialize report to JSON")
+/// Format report as JSON, with a `groups` summary if `group_by` is given
+fn format_json(report: &DebtReport, group_by: &[GroupKey]) -> Result<String> {
+    if group_by.is_empty() {
+        return serde_json::to_string_pretty(report).context("Failed to serialize report to JSON");
+    }
+
+    let mut value = serde_json::to_value(report).context("Failed to serialize report to JSON")?;
+    value["groups"] = serde_json::to_value(report.group_by(group_by))
+        .context("Failed to serialize groups to JSON")?;
+    serde_json::to_string_pretty(&value).context("Failed to serialize report to JSON")
+}
+
+/// Schema URI written into SARIF logs
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// SARIF result level for a severity
+fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low | Severity::Info => "note",
+    }
+}
+
+/// URI reference for a reported file: relative paths become `src/a.rs`,
+/// absolute ones `file:///...`
+fn sarif_uri(path: &Path) -> String {
+    let relative = path
+        .components()
+        .filter(|component| !matches!(component, Component::CurDir | Component::RootDir))
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let mut uri = String::new();
+    for ch in relative.chars() {
+        match ch {
+            '%' => uri.push_str("%25"),
+            ' ' => uri.push_str("%20"),
+            '#' => uri.push_str("%23"),
+            '?' => uri.push_str("%3F"),
+            _ => uri.push(ch),
+        }
+    }
+
+    if path.is_absolute() {
+        format!("file:///{}", uri)
+    } else {
+        uri
+    }
+}
+
+/// Region covering a marker from its keyword to the end of its comment
+///
+/// Columns are 1-based UTF-16 code units, the SARIF default.
+fn sarif_region(marker: &DebtMarker) -> Value {
+    let start_column = marker
+        .line_content
+        .find(&marker.marker_type)
+        .map_or(1, |idx| {
+            marker.line_content[..idx].encode_utf16().count() + 1
+        });
+    let last_line = marker
+        .continuation_lines
+        .last()
+        .unwrap_or(&marker.line_content);
+
+    let mut snippet = marker.line_content.clone();
+    for line in &marker.continuation_lines {
+        snippet.push('\n');
+        snippet.push_str(line);
+    }
+
+    json!({
+        "startLine": marker.line_number,
+        "startColumn": start_column,
+        "endLine": marker.line_number + marker.continuation_lines.len(),
+        "endColumn": last_line.encode_utf16().count().max(start_column - 1) + 1,
+        "snippet": { "text": snippet },
+    })
+}
+
+/// SARIF result for a marker, located at its repository path
+fn sarif_result(
+    marker: &DebtMarker,
+    path: &Path,
+    rule_index: usize,
+    baseline_state: Option<&str>,
+) -> Value {
+    let message = if marker.description.is_empty() {
+        marker.marker_type.clone()
+    } else {
+        format!("{}: {}", marker.marker_type, marker.description)
+    };
+
+    let mut properties = json!({
+        "severity": marker.severity,
+        "score": marker.score,
+        "kind": marker.kind,
+    });
+    if let Some(assignee) = &marker.assignee {
+        properties["assignee"] = json!(assignee);
+    }
+    if !marker.issue_refs.is_empty() {
+        properties["issueRefs"] = json!(marker.issue_refs);
+    }
+    if let Some(git_info) = &marker.git_info {
+        properties["author"] = json!(git_info.author);
+        properties["commit"] = json!(git_info.commit_hash);
+        properties["ageDays"] = json!(git_info.age_days);
+    }
+
+    let mut result = json!({
+        "ruleId": marker.marker_type,
+        "ruleIndex": rule_index,
+        "level": sarif_level(marker.severity),
+        "message": { "text": message },
+        "locations": [{
+            "physicalLocation": {
+                "artifactLocation": { "uri": sarif_uri(path) },
+                "region": sarif_region(marker),
+            }
+        }],
+        "properties": properties,
+    });
+    if !marker.fingerprint.is_empty() {
+        result["partialFingerprints"] = json!({ "fossilFingerprint/v1": marker.fingerprint });
+    }
+    if let Some(state) = baseline_state {
+        result["baselineState"] = json!(state);
+    }
+    result
+}
+
+/// SARIF 2.1.0 log with one rule per marker type and one result per marker
+///
+/// Result URIs are relative to the repository root (see
+/// [`fingerprint::repo_path`]) so code scanning can resolve them.
+fn sarif_log(
+    markers: &[&DebtMarker],
+    scan_path: &Path,
+    prefix: &Path,
+    baseline_state: Option<&str>,
+) -> Value {
+    let mut rule_ids: Vec<&str> = markers.iter().map(|m| m.marker_type.as_str()).collect();
+    rule_ids.sort_unstable();
+    rule_ids.dedup();
+
+    let rules: Vec<Value> = rule_ids
+        .iter()
+        .map(|&id| {
+            // Severity comes from the marker type, so any marker of it will do
+            let severity = markers
+                .iter()
+                .find(|m| m.marker_type == id)
+                .map(|m| m.severity)
+                .unwrap_or_default();
+            json!({
+                "id": id,
+                "shortDescription": { "text": format!("{} marker", id) },
+                "defaultConfiguration": { "level": sarif_level(severity) },
+                "properties": { "tags": ["technical-debt"], "severity": severity },
+            })
+        })
+        .collect();
+
+    let results: Vec<Value> = markers
+        .iter()
+        .map(|marker| {
+            let rule_index = rule_ids
+                .binary_search(&marker.marker_type.as_str())
+                .unwrap_or_default();
+            let path = fingerprint::repo_path(marker, scan_path, prefix);
+            sarif_result(marker, &path, rule_index, baseline_state)
+        })
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "fossil",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules,
+                }
+            },
+            "columnKind": "utf16CodeUnits",
+            "results": results,
+        }]
+    })
+}
+
+/// Format report as SARIF 2.1.0
+fn format_sarif(report: &DebtReport, prefix: &Path) -> Result<String> {
+    let markers: Vec<&DebtMarker> = report.markers.iter().collect();
+    serde_json::to_string_pretty(&sarif_log(&markers, &report.scan_path, prefix, None))
+        .context("Failed to serialize report to SARIF")
+}
+
+/// Format a diff as SARIF 2.1.0: only added markers, with baseline state `new`
+fn format_diff_sarif(diff: &MarkerDiff) -> Result<String> {
+    let markers: Vec<&DebtMarker> = diff.added.iter().collect();
+    serde_json::to_string_pretty(&sarif_log(
+        &markers,
+        Path::new(""),
+        Path::new(""),
+        Some("new"),
+    ))
+    .context("Failed to serialize diff to SARIF")
+}
+
+/// The final NDJSON record: the report without its markers, under a
+/// `summary` key
+pub fn ndjson_summary(report: &DebtReport) -> Result<String> {
+    let mut summary = serde_json::to_value(report).context("Failed to serialize summary")?;
+    if let Some(fields) = summary.as_object_mut() {
+        fields.remove("markers");
+    }
+    Ok(json!({ "summary": summary }).to_string())
+}
+
+/// Format report as NDJSON: one marker per line, then the summary
+///
+/// `fossil scan` streams this format instead (see
+/// [`crate::stream::stream_ndjson`]); this renders a report already in memory.
+fn format_ndjson(report: &DebtReport) -> Result<String> {
+    let mut lines = Vec::with_capacity(report.markers.len() + 1);
+    for marker in &report.markers {
+        lines.push(serde_json::to_string(marker).context("Failed to serialize marker to JSON")?);
+    }
+    lines.push(ndjson_summary(report)?);
+    Ok(lines.join("\n"))
+}
+
+/// Code Climate severity for a marker severity
+fn codeclimate_severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical => "blocker",
+        Severity::High => "critical",
+        Severity::Medium => "major",
+        Severity::Low => "minor",
+        Severity::Info => "info",
+    }
+}
+
+/// Code Climate issues for markers found under `scan_path`
+///
+/// Paths are relative to the repository root (`prefix` locates `scan_path`
+/// in it), as GitLab expects. Identical markers in one file share a
+/// fingerprint, so repeats get `:2`, `:3`, ... appended to keep each issue
+/// distinct.
+fn codeclimate_issues(markers: &[&DebtMarker], scan_path: &Path, prefix: &Path) -> Value {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let issues: Vec<Value> = markers
+        .iter()
+        .map(|marker| {
+            let path = fingerprint::repo_path(marker, scan_path, prefix);
+            let mut id = if marker.fingerprint.is_empty() {
+                fingerprint::fingerprint(marker, &path)
+            } else {
+                marker.fingerprint.clone()
+            };
+            let count = seen.entry(id.clone()).or_default();
+            *count += 1;
+            if *count > 1 {
+                id = format!("{}:{}", id, count);
+            }
+
+            let description = if marker.description.is_empty() {
+                marker.marker_type.clone()
+            } else {
+                format!("{}: {}", marker.marker_type, marker.description)
+            };
+            json!({
+                "type": "issue",
+                "engine_name": "fossil",
+                "check_name": marker.marker_type,
+                "description": description,
+                "severity": codeclimate_severity(marker.severity),
+                "fingerprint": id,
+                "location": {
+                    "path": path.to_string_lossy().replace('\\', "/"),
+                    "lines": { "begin": marker.line_number },
+                },
+            })
+        })
+        .collect();
+    Value::Array(issues)
+}
+
+/// Format report as Code Climate issues (GitLab code quality)
+fn format_codeclimate(report: &DebtReport, prefix: &Path) -> Result<String> {
+    let markers: Vec<&DebtMarker> = report.markers.iter().collect();
+    serde_json::to_string_pretty(&codeclimate_issues(&markers, &report.scan_path, prefix))
+        .context("Failed to serialize report to Code Climate JSON")
+}
+
+/// Format a diff as Code Climate issues: only added markers
+fn format_diff_codeclimate(diff: &MarkerDiff) -> Result<String> {
+    let markers: Vec<&DebtMarker> = diff.added.iter().collect();
+    serde_json::to_string_pretty(&codeclimate_issues(&markers, Path::new(""), Path::new("")))
+        .context("Failed to serialize diff to Code Climate JSON")
+}
+
+/// Value of a CSV/TSV column for a marker (empty when unknown)
+fn column_value(marker: &DebtMarker, column: Column) -> String {
+    let git_info = marker.git_info.as_ref();
+    match column {
+        Column::Type => marker.marker_type.clone(),
+        Column::Severity => marker.severity.to_string(),
+        Column::File => marker.file_path.display().to_string(),
+        Column::Line => marker.line_number.to_string(),
+        Column::Author => git_info.map(|i| i.author.clone()).unwrap_or_default(),
+        Column::Email => git_info.map(|i| i.author_email.clone()).unwrap_or_default(),
+        Column::AgeDays => git_info.map(|i| i.age_days.to_string()).unwrap_or_default(),
+        Column::Commit => git_info.map(|i| i.commit_hash.clone()).unwrap_or_default(),
+        Column::Assignee => marker.assignee.clone().unwrap_or_default(),
+        Column::Score => marker.score.to_string(),
+        Column::Description => marker.description.clone(),
+        Column::LineContent => marker.line_content.clone(),
+    }
+}
+
+/// Encode a field for CSV or TSV
+///
+/// Fields starting with `=`, `+`, `-` or `@` get a leading `'` so spreadsheets
+/// don't run marker text as a formula. CSV fields containing the delimiter,
+/// quotes or line breaks are quoted as in RFC 4180. TSV has no quoting, so
+/// backslashes, tabs and line breaks are escaped as `\\`, `\t`, `\n` and `\r`.
+fn delimited_field(value: &str, delimiter: char) -> String {
+    let escaped;
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        escaped = format!("'{}", value);
+        &escaped
+    } else {
+        value
+    };
+
+    if delimiter == '\t' {
+        value
+            .replace('\\', "\\\\")
+            .replace('\t', "\\t")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r")
+    } else if value.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Join rows of fields into CSV or TSV lines
+fn delimited_rows(rows: Vec<Vec<String>>, delimiter: char) -> String {
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .map(|field| delimited_field(field, delimiter))
+                .collect::<Vec<_>>()
+                .join(&delimiter.to_string())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Format report as CSV or TSV: a header row, then one row per marker
+fn format_delimited(report: &DebtReport, columns: &[Column], delimiter: char) -> String {
+    let header = columns.iter().map(|c| c.as_str().to_string()).collect();
+    let rows = report
+        .markers
+        .iter()
+        .map(|marker| columns.iter().map(|&c| column_value(marker, c)).collect());
+    delimited_rows(std::iter::once(header).chain(rows).collect(), delimiter)
+}
+
+/// Format a diff as CSV or TSV, with a leading `change` column
+/// (`added`, `removed` or `moved`)
+fn format_diff_delimited(diff: &MarkerDiff, columns: &[Column], delimiter: char) -> String {
+    let header = std::iter::once("change")
+        .chain(columns.iter().map(|c| c.as_str()))
+        .map(str::to_string)
+        .collect();
+    let changes = (diff.added.iter().map(|m| ("added", m)))
+        .chain(diff.removed.iter().map(|m| ("removed", m)))
+        .chain(diff.moved.iter().map(|m| ("moved", &m.marker)));
+    let rows = changes.map(|(change, marker)| {
+        std::iter::once(change.to_string())
+            .chain(columns.iter().map(|&c| column_value(marker, c)))
+            .collect()
+    });
+    delimited_rows(std::iter::once(header).chain(rows).collect(), delimiter)
+}
+
+/// Escape text for XML, dropping control characters XML 1.0 doesn't allow
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c < ' ' || c == '\u{FFFE}' || c == '\u{FFFF}' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A JUnit test case for a marker: failed at or above `fail_on`, otherwise
+/// skipped
+fn junit_marker_case(marker: &DebtMarker, fail_on: Severity) -> String {
+    let file = marker.file_path.display().to_string();
+    let mut name = format!("{} at line {}", marker.marker_type, marker.line_number);
+    if !marker.description.is_empty() {
+        name.push_str(&format!(": {}", marker.description));
+    }
+    let mut case = format!(
+        "    <testcase name=\"{}\" classname=\"{}\" file=\"{}\" line=\"{}\">\n",
+        xml_escape(&name),
+        xml_escape(&file),
+        xml_escape(&file),
+        marker.line_number
+    );
+
+    if marker.severity >= fail_on {
+        let mut details = vec![
+            format!("{}:{}", file, marker.line_number),
+            marker.line_content.trim().to_string(),
+            format!("Severity: {}", marker.severity),
+        ];
+        if let Some(assignee) = &marker.assignee {
+            details.push(format!("Assignee: {}", assignee));
+        }
+        if let Some(git_info) = &marker.git_info {
+            details.push(format!(
+                "Author: {} <{}> ({} days old, commit {})",
+                git_info.author, git_info.author_email, git_info.age_days, git_info.commit_hash
+            ));
+        }
+        case.push_str(&format!(
+            "      <failure message=\"{}\" type=\"{}\">{}</failure>\n",
+            xml_escape(&marker.description),
+            xml_escape(&marker.marker_type),
+            xml_escape(&details.join("\n"))
+        ));
+    } else {
+        case.push_str(&format!(
+            "      <skipped message=\"{} severity is below {}\"/>\n",
+            marker.severity, fail_on
+        ));
+    }
+
+    case.push_str("    </testcase>\n");
+    case
+}
+
+/// A JUnit test case for a policy violation, always failed
+fn junit_violation_case(violation: &Violation) -> String {
+    let (name, location) = match (&violation.file_path, violation.line_number) {
+        (Some(path), Some(line)) => {
+            let path = path.display().to_string();
+            (
+                format!("{} at {}:{}", violation.rule, path, line),
+                format!(" file=\"{}\" line=\"{}\"", xml_escape(&path), line),
+            )
+        }
+        _ => (violation.rule.clone(), String::new()),
+    };
+    format!(
+        "    <testcase name=\"{}\" classname=\"fossil.policy\"{}>\n      \
+         <failure message=\"{}\" type=\"{}\"/>\n    </testcase>\n",
+        xml_escape(&name),
+        location,
+        xml_escape(&violation.message),
+        xml_escape(&violation.rule)
+    )
+}
+
+/// Format report as JUnit XML
+///
+/// Each file is a test suite and each marker a test case, failed if its
+/// severity is at least `fail_on` and skipped otherwise. Policy violations
+/// are failed test cases in a final `fossil policy` suite.
+fn format_junit(report: &DebtReport, violations: &[Violation], fail_on: Severity) -> String {
+    let mut by_file: BTreeMap<&Path, Vec<&DebtMarker>> = BTreeMap::new();
+    for marker in &report.markers {
+        by_file.entry(&marker.file_path).or_default().push(marker);
+    }
+
+    let timestamp = report.scan_time.format("%Y-%m-%dT%H:%M:%S");
+    let mut suites = String::new();
+    let (mut tests, mut failures) = (0, 0);
+    let suite_header = |name: &str, tests: usize, failures: usize, skipped: usize| {
+        format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" \
+             skipped=\"{}\" timestamp=\"{}\">\n",
+            xml_escape(name),
+            tests,
+            failures,
+            skipped,
+            timestamp
+        )
+    };
+
+    for (file, mut markers) in by_file {
+        markers.sort_by_key(|m| m.line_number);
+        let failed = markers.iter().filter(|m| m.severity >= fail_on).count();
+        suites.push_str(&suite_header(
+            &file.display().to_string(),
+            markers.len(),
+            failed,
+            markers.len() - failed,
+        ));
+        for marker in &markers {
+            suites.push_str(&junit_marker_case(marker, fail_on));
+        }
+        suites.push_str("  </testsuite>\n");
+        tests += markers.len();
+        failures += failed;
+    }
+
+    if !violations.is_empty() {
+        suites.push_str(&suite_header(
+            "fossil policy",
+            violations.len(),
+            violations.len(),
+            0,
+        ));
+        for violation in violations {
+            suites.push_str(&junit_violation_case(violation));
+        }
+        suites.push_str("  </testsuite>\n");
+        tests += violations.len();
+        failures += violations.len();
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <testsuites name=\"fossil\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\">\n\
+         {}</testsuites>",
+        tests,
+        failures,
+        tests - failures,
+        suites
+    )
+}
+
+/// One-line summary of a diff, e.g. `+2 added, -1 removed, 0 moved, 5 unchanged`
+fn diff_summary(diff: &MarkerDiff) -> String {
+    format!(
+        "+{} added, -{} removed, {} moved, {} unchanged",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.moved.len(),
+        diff.unchanged
+    )
+}
+
+/// Format a diff as terminal tables
+fn format_diff_terminal(diff: &MarkerDiff) -> String {
+    let mut output = String::new();
+
+    // Header
+    let line = "─".repeat(58);
+    let range = format!(
+        "{} ({:.7}) → {} ({:.7})",
+        diff.base, diff.base_commit, diff.head, diff.head_commit
+    );
+    output.push_str(&format!("╭{}╮\n", line));
+    output.push_str(&format!("│ {:^56} │\n", "Fossil - Technical Debt Diff"));
+    output.push_str(&format!("│ {:<56} │\n", range));
+    output.push_str(&format!("│ {:<56} │\n", diff_summary(diff)));
+    output.push_str(&format!("╰{}╯\n\n", line));
+
+    let sections = [("Added", &diff.added), ("Removed", &diff.removed)];
+    for (title, markers) in sections {
+        if markers.is_empty() {
+            continue;
+        }
+
+        output.push_str(&format!("{}:\n", title));
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_header(vec![
+                Cell::new("Type").fg(Color::Cyan),
+                Cell::new("File").fg(Color::Cyan),
+                Cell::new("Line").fg(Color::Cyan),
+                Cell::new("Description").fg(Color::Cyan),
+            ]);
+        for marker in markers {
+            table.add_row(vec![
+                Cell::new(&marker.marker_type),
+                Cell::new(marker.file_path.display().to_string()),
+                Cell::new(marker.line_number.to_string()),
+                Cell::new(&marker.description),
+            ]);
+        }
+        output.push_str(&format!("{}\n\n", table));
+    }
+
+    if !diff.moved.is_empty() {
+        output.push_str("Moved:\n");
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_header(vec![
+                Cell::new("Type").fg(Color::Cyan),
+                Cell::new("From").fg(Color::Cyan),
+                Cell::new("To").fg(Color::Cyan),
+                Cell::new("Description").fg(Color::Cyan),
+            ]);
+        for moved in &diff.moved {
+            table.add_row(vec![
+                Cell::new(&moved.marker.marker_type),
+                Cell::new(format!("{}:{}", moved.from_path.display(), moved.from_line)),
+                Cell::new(format!(
+                    "{}:{}",
+                    moved.marker.file_path.display(),
+                    moved.marker.line_number
+                )),
+                Cell::new(&moved.marker.description),
+            ]);
+        }
+        output.push_str(&format!("{}\n", table));
+    }
+
+    output
+}
+
+/// Format a diff as Markdown, e.g. for a pull request comment
+fn format_diff_markdown(diff: &MarkerDiff) -> String {
+    let mut output = String::new();
+
+    output.push_str("# Fossil - Technical Debt Diff\n\n");
+    output.push_str(&format!(
+        "**Base**: `{}` ({:.7})\n",
+        diff.base, diff.base_commit
+    ));
+    output.push_str(&format!(
+        "**Head**: `{}` ({:.7})\n",
+        diff.head, diff.head_commit
+    ));
+    output.push_str(&format!("**Changes**: {}\n\n", diff_summary(diff)));
+
+    let sections = [("Added", &diff.added), ("Removed", &diff.removed)];
+    for (title, markers) in sections {
+        if markers.is_empty() {
+            continue;
+        }
+
+        output.push_str(&format!("## {} ({})\n\n", title, markers.len()));
+        for marker in markers {
+            output.push_str(&format!(
+                "- **{}** in `{}:{}`",
+                marker.marker_type,
+                marker.file_path.display(),
+                marker.line_number
+            ));
+            if !marker.description.is_empty() {
+                output.push_str(&format!(": {}", marker.description));
+            }
+            output.push('\n');
+        }
+        output.push('\n');
+    }
+
+    if !diff.moved.is_empty() {
+        output.push_str(&format!("## Moved ({})\n\n", diff.moved.len()));
+        for moved in &diff.moved {
+            output.push_str(&format!(
+                "- **{}** `{}:{}` → `{}:{}`",
+                moved.marker.marker_type,
+                moved.from_path.display(),
+                moved.from_line,
+                moved.marker.file_path.display(),
+                moved.marker.line_number
+            ));
+            if !moved.marker.description.is_empty() {
+                output.push_str(&format!(": {}", moved.marker.description));
+            }
+            output.push('\n');
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Format a diff as JSON
+fn format_diff_json(diff: &MarkerDiff) -> Result<String> {
+    serde_json::to_string_pretty(diff).context("Failed to serialize diff to JSON")
 }
 
 #[cfg(test)]
@@ -226,35 +1368,27 @@ mod tests {
 
     fn create_test_report() -> DebtReport {
         let marker1 = DebtMarker {
-            marker_type: "TODO".to_string(),
-            file_path: PathBuf::from("src/main.rs"),
-            line_number: 42,
-            line_content: "// TODO: implement this".to_string(),
+            line_content: "// TODO(alice): implement this, see #12".to_string(),
+            description: "implement this, see #12".to_string(),
+            assignee: Some("alice".to_string()),
+            issue_refs: vec!["#12".to_string()],
             context_before: vec!["fn main() {".to_string()],
             context_after: vec!["    println!(\"hello\");".to_string()],
-            git_info: Some(GitBlameInfo {
-                author: "Alice".to_string(),
-                author_email: "alice@example.com".to_string(),
-                commit_hash: "abc123".to_string(),
-                commit_time: Utc::now(),
-                age_days: 100,
-            }),
+            git_info: Some(GitBlameInfo::test("Alice", 100)),
+            score: 2.5,
+            ..DebtMarker::test("src/main.rs", 42, "TODO")
         };
 
         let marker2 = DebtMarker {
-            marker_type: "FIXME".to_string(),
-            file_path: PathBuf::from("src/lib.rs"),
-            line_number: 10,
+            severity: Severity::High,
             line_content: "// FIXME: broken".to_string(),
-            context_before: vec![],
-            context_after: vec![],
+            description: "broken".to_string(),
             git_info: Some(GitBlameInfo {
-                author: "Bob".to_string(),
-                author_email: "bob@example.com".to_string(),
                 commit_hash: "def456".to_string(),
-                commit_time: Utc::now(),
-                age_days: 50,
+                ..GitBlameInfo::test("Bob", 50)
             }),
+            score: 5.0,
+            ..DebtMarker::test("src/lib.rs", 10, "FIXME")
         };
 
         DebtReport::new(vec![marker1, marker2], PathBuf::from("/test/project"))
@@ -263,38 +1397,458 @@ mod tests {
     #[test]
     fn test_format_terminal() {
         let report = create_test_report();
-        let output = format_terminal(&report, 10);
+        let output = format_terminal(&report, 10, SortKey::Age, SortOrder::Desc, &[]);
 
         assert!(output.contains("Fossil - Technical Debt Report"));
         assert!(output.contains("Total Markers: 2"));
         assert!(output.contains("TODO"));
         assert!(output.contains("FIXME"));
         assert!(output.contains("Alice"));
+        assert!(output.contains("Summary by Severity:"));
+        assert!(output.contains("high"));
+        assert!(output.contains("Debt Index: 7.5"));
+        assert!(output.contains("Debt Score by Directory:"));
+        assert!(output.contains("Top 2 Oldest Markers:"));
     }
 
     #[test]
     fn test_format_markdown() {
         let report = create_test_report();
-        let output = format_markdown(&report, 10);
+        let output = format_markdown(&report, 10, SortKey::Age, SortOrder::Desc, &[]);
 
         assert!(output.contains("# Fossil - Technical Debt Report"));
         assert!(output.contains("**Total Markers**: 2"));
         assert!(output.contains("## Summary by Type"));
+        assert!(output.contains("## Summary by Severity\n\n- **high**: 1\n- **medium**: 1\n"));
+        assert!(output.contains("- Severity: high"));
         assert!(output.contains("TODO"));
         assert!(output.contains("Alice"));
+        assert!(output.contains("- Assignee: alice"));
+        assert!(output.contains("- Issues: #12"));
+        assert!(output.contains("**Debt Index**: 7.5"));
+        assert!(
+            output.contains("## Debt Score by File\n\n- `src/lib.rs`: 5.0\n- `src/main.rs`: 2.5")
+        );
+        assert!(output.contains("1. **TODO** in `src/main.rs:42`"));
+
+        let by_score = format_markdown(&report, 1, SortKey::Score, SortOrder::Desc, &[]);
+        assert!(by_score.contains("## Top 1 Highest-Scoring Markers\n\n1. **FIXME**"));
+        assert!(by_score.contains("- Score: 5.0"));
+    }
+
+    #[test]
+    fn test_group_by() {
+        let report = create_test_report();
+        let group_by = [GroupKey::Dir, GroupKey::Severity];
+
+        let terminal = format_terminal(&report, 10, SortKey::File, SortOrder::Asc, &group_by);
+        assert!(terminal.contains("Summary by Directory, Severity:"));
+        assert!(terminal.contains("│ src       ┆          ┆ 2     ┆ 7.5   │"));
+        assert!(terminal.contains("│           ┆ high     ┆ 1     ┆ 5.0   │"));
+        assert!(!terminal.contains("Summary by Type:"));
+        assert!(terminal.contains("Top 2 Markers by File:"));
+
+        let markdown = format_markdown(&report, 10, SortKey::Type, SortOrder::Desc, &group_by);
+        assert!(markdown.contains(
+            "## Summary by Directory, Severity\n\n- **src**: 2 (score 7.5)\n  - **high**: 1 (score 5.0)\n  - **medium**: 1 (score 2.5)\n\n"
+        ));
+        assert!(markdown.contains("## Top 2 Markers by Type (Descending)\n\n1. **TODO**"));
+
+        let json: Value = serde_json::from_str(&format_json(&report, &group_by).unwrap()).unwrap();
+        assert_eq!(json["groups"][0]["key"], "src");
+        assert_eq!(json["groups"][0]["groups"][1]["key"], "medium");
+        assert!(json["groups"][0]["groups"][1].get("groups").is_none());
+        assert_eq!(json["total_count"], 2);
+
+        let options = ReportOptions {
+            top_n: 10,
+            sort_by: SortKey::Age,
+            sort_order: SortOrder::Desc,
+            group_by: &group_by,
+            count_only: false,
+            template: None,
+            columns: &[],
+            junit_fail_on: Severity::Info,
+            violations: &[],
+            prefix: Path::new(""),
+        };
+        let error = generate_report(&report, OutputFormat::Csv, None, &options).unwrap_err();
+        assert!(error.to_string().contains("--group-by is only supported"));
+    }
+
+    #[test]
+    fn test_builtin_templates() {
+        let mut markers = create_test_report().markers;
+        markers[0].continuation_lines = vec!["// and then some".to_string()];
+        if let Some(git_info) = &mut markers[1].git_info {
+            git_info.introduced_by = Some("Carol".to_string());
+            git_info.introduced_at = Some(Utc::now());
+        }
+        markers.push(DebtMarker {
+            file_path: PathBuf::from("README.md"),
+            git_info: None,
+            score: 0.0,
+            ..markers[1].clone()
+        });
+        let report = DebtReport::new(markers, PathBuf::from("/test/project"));
+        let empty = DebtReport::new(vec![], PathBuf::from("/test/project"));
+
+        for report in [&report, &empty] {
+            let cases: [(usize, SortKey, SortOrder, &[GroupKey]); 4] = [
+                (10, SortKey::Age, SortOrder::Desc, &[]),
+                (1, SortKey::Score, SortOrder::Desc, &[]),
+                (10, SortKey::Author, SortOrder::Asc, &[GroupKey::Type]),
+                (
+                    10,
+                    SortKey::File,
+                    SortOrder::Desc,
+                    &[GroupKey::Dir, GroupKey::Severity],
+                ),
+            ];
+            for (top_n, sort_by, order, group_by) in cases {
+                let terminal = template::format_template(
+                    report,
+                    "builtin:terminal",
+                    top_n,
+                    sort_by,
+                    order,
+                    group_by,
+                )
+                .unwrap();
+                assert_eq!(
+                    terminal,
+                    format_terminal(report, top_n, sort_by, order, group_by)
+                );
+                let markdown = template::format_template(
+                    report,
+                    "builtin:markdown",
+                    top_n,
+                    sort_by,
+                    order,
+                    group_by,
+                )
+                .unwrap();
+                assert_eq!(
+                    markdown,
+                    format_markdown(report, top_n, sort_by, order, group_by)
+                );
+            }
+        }
     }
 
     #[test]
     fn test_format_json() {
         let report = create_test_report();
-        let output = format_json(&report).unwrap();
+        let output = format_json(&report, &[]).unwrap();
 
         assert!(output.contains("\"marker_type\""));
         assert!(output.contains("TODO"));
         assert!(output.contains("alice@example.com"));
+        assert!(output.contains("\"assignee\": \"alice\""));
 
         // Verify it's valid JSON
         let parsed: serde_json::Value = serde_json::from_str(&output).unwrap();
         assert_eq!(parsed["total_count"], 2);
+        assert_eq!(parsed["by_severity"]["high"], 1);
+        assert_eq!(parsed["markers"][1]["severity"], "high");
+    }
+
+    fn create_test_diff() -> MarkerDiff {
+        let report = create_test_report();
+        let mut markers = report.markers.into_iter();
+        let added = markers.next().unwrap();
+        let removed = markers.next().unwrap();
+        let mut moved = added.clone();
+        moved.file_path = PathBuf::from("src/moved.rs");
+
+        MarkerDiff {
+            base: "main".to_string(),
+            head: "HEAD".to_string(),
+            base_commit: "1111111111".to_string(),
+            head_commit: "2222222222".to_string(),
+            added: vec![added],
+            removed: vec![removed],
+            moved: vec![crate::diff::MovedMarker {
+                from_path: PathBuf::from("src/old.rs"),
+                from_line: 3,
+                marker: moved,
+            }],
+            unchanged: 4,
+        }
+    }
+
+    #[test]
+    fn test_format_diff() {
+        let diff = create_test_diff();
+
+        let terminal = format_diff_terminal(&diff);
+        assert!(terminal.contains("main (1111111) → HEAD (2222222)"));
+        assert!(terminal.contains("+1 added, -1 removed, 1 moved, 4 unchanged"));
+        assert!(terminal.contains("src/old.rs:3"));
+
+        let markdown = format_diff_markdown(&diff);
+        assert!(markdown.contains("## Added (1)"));
+        assert!(markdown.contains("## Removed (1)"));
+        assert!(markdown.contains("`src/old.rs:3` → `src/moved.rs:"));
+
+        let json = format_diff_json(&diff).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["unchanged"], 4);
+        assert_eq!(parsed["moved"][0]["from_line"], 3);
+    }
+
+    #[test]
+    fn test_format_delimited() {
+        let mut report = create_test_report();
+        report.markers[1].line_content = "// FIXME: broken, \"badly\"\n\tcontinued".to_string();
+        report.markers[1].git_info = None;
+        let columns = [
+            Column::Type,
+            Column::Line,
+            Column::Author,
+            Column::LineContent,
+        ];
+
+        let csv = format_delimited(&report, &columns, ',');
+        assert_eq!(
+            csv,
+            "type,line,author,line_content\n\
+             TODO,42,Alice,\"// TODO(alice): implement this, see #12\"\n\
+             FIXME,10,,\"// FIXME: broken, \"\"badly\"\"\n\tcontinued\""
+        );
+
+        let tsv = format_delimited(&report, &columns, '\t');
+        let lines: Vec<&str> = tsv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[2],
+            "FIXME\t10\t\t// FIXME: broken, \"badly\"\\n\\tcontinued"
+        );
+
+        // Formula-like fields are defused for spreadsheets
+        report.markers[0].line_content = "=HYPERLINK(\"http://x\")".to_string();
+        report.markers[1].line_content = "@SUM(A1)".to_string();
+        let csv = format_delimited(&report, &columns, ',');
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[1], "TODO,42,Alice,\"'=HYPERLINK(\"\"http://x\"\")\"");
+        assert_eq!(lines[2], "FIXME,10,,'@SUM(A1)");
+        let tsv = format_delimited(&report, &columns, '\t');
+        assert!(tsv.ends_with("\t'@SUM(A1)"));
+        assert_eq!(delimited_field("+1", ','), "'+1");
+        assert_eq!(delimited_field("-1", ','), "'-1");
+        assert_eq!(delimited_field("a=b", ','), "a=b");
+
+        let diff = format_diff_delimited(&create_test_diff(), &[Column::File], ',');
+        assert_eq!(
+            diff,
+            "change,file\nadded,src/main.rs\nremoved,src/lib.rs\nmoved,src/moved.rs"
+        );
+    }
+
+    #[test]
+    fn test_format_junit() {
+        let mut report = create_test_report();
+        report.markers[0].description = "use <T> & \"friends\"\u{1b}".to_string();
+        let violations = vec![
+            Violation {
+                rule: "max_total".to_string(),
+                message: "2 markers exceed the limit of 1".to_string(),
+                file_path: None,
+                line_number: None,
+                marker_type: None,
+            },
+            Violation {
+                rule: "require_assignee".to_string(),
+                message: "FIXME has no assignee".to_string(),
+                file_path: Some(PathBuf::from("src/lib.rs")),
+                line_number: Some(10),
+                marker_type: Some("FIXME".to_string()),
+            },
+        ];
+
+        let xml = format_junit(&report, &violations, Severity::High);
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+        assert!(xml.contains(
+            "<testsuites name=\"fossil\" tests=\"4\" failures=\"3\" errors=\"0\" skipped=\"1\">"
+        ));
+
+        // Medium is below the threshold
+        assert!(xml.contains(
+            "<testcase name=\"TODO at line 42: use &lt;T&gt; &amp; &quot;friends&quot;\" \
+             classname=\"src/main.rs\" file=\"src/main.rs\" line=\"42\">\n      \
+             <skipped message=\"medium severity is below high\"/>"
+        ));
+        assert!(xml.contains("<testsuite name=\"src/lib.rs\" tests=\"1\" failures=\"1\""));
+        assert!(xml.contains("<failure message=\"broken\" type=\"FIXME\">src/lib.rs:10\n"));
+        assert!(xml.contains("Author: Bob &lt;bob@example.com&gt; (50 days old"));
+
+        assert!(xml.contains("<testsuite name=\"fossil policy\" tests=\"2\" failures=\"2\""));
+        assert!(xml.contains("<testcase name=\"max_total\" classname=\"fossil.policy\">"));
+        assert!(xml.contains(
+            "<testcase name=\"require_assignee at src/lib.rs:10\" classname=\"fossil.policy\" \
+             file=\"src/lib.rs\" line=\"10\">"
+        ));
+        assert!(xml.ends_with("</testsuites>"));
+
+        // Everything fails at the lowest threshold
+        let xml = format_junit(&report, &[], Severity::Info);
+        assert!(xml.contains("tests=\"2\" failures=\"2\" errors=\"0\" skipped=\"0\""));
+        assert!(!xml.contains("fossil policy"));
+    }
+
+    #[test]
+    fn test_format_codeclimate() {
+        let mut report = create_test_report();
+        report.scan_path = PathBuf::from("src");
+        let mut duplicate = report.markers[1].clone();
+        duplicate.line_number = 20;
+        report.markers.push(duplicate);
+        report.markers[1].fingerprint = "0123456789abcdef".to_string();
+        report.markers[2].fingerprint = "0123456789abcdef".to_string();
+
+        let output = format_codeclimate(&report, Path::new("crates/core")).unwrap();
+        let issues: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(issues.as_array().unwrap().len(), 3);
+        assert_eq!(
+            issues[1],
+            json!({
+                "type": "issue",
+                "engine_name": "fossil",
+                "check_name": "FIXME",
+                "description": "FIXME: broken",
+                "severity": "critical",
+                "fingerprint": "0123456789abcdef",
+                "location": { "path": "crates/core/lib.rs", "lines": { "begin": 10 } },
+            })
+        );
+        assert_eq!(issues[0]["severity"], "major");
+        assert_eq!(issues[2]["fingerprint"], "0123456789abcdef:2");
+
+        // Without a fingerprint one is computed from the repository path
+        assert_eq!(
+            issues[0]["fingerprint"],
+            fingerprint::fingerprint(&report.markers[0], Path::new("crates/core/main.rs"))
+        );
+
+        let diff = format_diff_codeclimate(&create_test_diff()).unwrap();
+        let issues: serde_json::Value = serde_json::from_str(&diff).unwrap();
+        assert_eq!(issues.as_array().unwrap().len(), 1);
+        assert_eq!(issues[0]["location"]["path"], "src/main.rs");
+    }
+
+    /// Errors from validating a SARIF log against the vendored SARIF 2.1.0
+    /// schema subset
+    // TODO: vendor the official schema unchanged, from
+    // https://docs.oasis-open.org/sarif/sarif/v2.1.0/errata01/os/schemas/sarif-schema-2.1.0.json,
+    // as tests/fixtures/sarif-schema-2.1.0.json and validate against it instead
+    // of the hand-trimmed subset
+    fn sarif_errors(log: &serde_json::Value) -> Vec<String> {
+        let schema: serde_json::Value = serde_json::from_str(include_str!(
+            "../tests/fixtures/sarif-schema-2.1.0-subset.json"
+        ))
+        .unwrap();
+        let validator = jsonschema::options()
+            .should_validate_formats(true)
+            .build(&schema)
+            .unwrap();
+        validator.iter_errors(log).map(|e| e.to_string()).collect()
+    }
+
+    fn assert_valid_sarif(output: &str) -> serde_json::Value {
+        let log: serde_json::Value = serde_json::from_str(output).unwrap();
+        let errors = sarif_errors(&log);
+        assert!(errors.is_empty(), "invalid SARIF: {:#?}", errors);
+        log
+    }
+
+    #[test]
+    fn test_format_sarif() {
+        let mut report = create_test_report();
+        report.markers[0].fingerprint = "0123456789abcdef".to_string();
+        report.markers[1].continuation_lines = vec!["//   badly".to_string()];
+
+        let log = assert_valid_sarif(&format_sarif(&report, Path::new("")).unwrap());
+        assert_eq!(log["version"], "2.1.0");
+
+        let run = &log["runs"][0];
+        let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+        let rule_ids: Vec<_> = rules.iter().map(|r| r["id"].as_str().unwrap()).collect();
+        assert_eq!(rule_ids, vec!["FIXME", "TODO"]);
+        assert_eq!(rules[0]["defaultConfiguration"]["level"], "error");
+
+        let todo = &run["results"][0];
+        assert_eq!(todo["ruleId"], "TODO");
+        assert_eq!(todo["ruleIndex"], 1);
+        assert_eq!(todo["level"], "warning");
+        assert_eq!(todo["message"]["text"], "TODO: implement this, see #12");
+        assert_eq!(
+            todo["partialFingerprints"]["fossilFingerprint/v1"],
+            "0123456789abcdef"
+        );
+        let location = &todo["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "src/main.rs");
+        assert_eq!(location["region"]["startLine"], 42);
+        assert_eq!(location["region"]["startColumn"], 4);
+
+        // Continuation lines extend the region; no fingerprint, no partialFingerprints
+        let fixme = &run["results"][1];
+        assert_eq!(
+            fixme["locations"][0]["physicalLocation"]["region"]["endLine"],
+            11
+        );
+        assert!(fixme.get("partialFingerprints").is_none());
+
+        // The schema catches mistakes
+        let mut broken = log.clone();
+        broken["runs"][0]["results"][0]["level"] = json!("fatal");
+        broken["runs"][0]["results"][1]["locations"][0]["physicalLocation"]["region"]["startLine"] =
+            json!(0);
+        assert_eq!(sarif_errors(&broken).len(), 2);
+    }
+
+    #[test]
+    fn test_format_sarif_subdirectory() {
+        // `fossil scan crates/core` from the repository root
+        let mut report = create_test_report();
+        report.scan_path = PathBuf::from("./crates/core");
+        report.markers[0].file_path = PathBuf::from("./crates/core/src/main.rs");
+        report.markers[1].file_path = PathBuf::from("./crates/core/src/lib.rs");
+        let log = assert_valid_sarif(&format_sarif(&report, Path::new("crates/core")).unwrap());
+        let uri = |idx: usize| {
+            log["runs"][0]["results"][idx]["locations"][0]["physicalLocation"]["artifactLocation"]
+                ["uri"]
+                .clone()
+        };
+        assert_eq!(uri(0), "crates/core/src/main.rs");
+        assert_eq!(uri(1), "crates/core/src/lib.rs");
+
+        // Scanned from inside the subdirectory
+        report.scan_path = PathBuf::from(".");
+        report.markers[0].file_path = PathBuf::from("./src/main.rs");
+        report.markers[1].file_path = PathBuf::from("./src/lib.rs");
+        let log = assert_valid_sarif(&format_sarif(&report, Path::new("crates/core")).unwrap());
+        assert_eq!(
+            log["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "crates/core/src/main.rs"
+        );
+    }
+
+    #[test]
+    fn test_format_diff_sarif() {
+        let diff = create_test_diff();
+        let log = assert_valid_sarif(&format_diff_sarif(&diff).unwrap());
+
+        let results = log["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), diff.added.len());
+        assert_eq!(results[0]["baselineState"], "new");
+    }
+
+    #[test]
+    fn test_sarif_uri() {
+        assert_eq!(sarif_uri(Path::new("./src/my file.rs")), "src/my%20file.rs");
+        assert_eq!(
+            sarif_uri(Path::new("/repo/a#1.rs")),
+            "file:///repo/a%231.rs"
+        );
     }
 }
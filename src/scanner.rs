@@ -1,23 +1,57 @@
-use crate::models::{Config, DebtMarker};
+use crate::fingerprint;
+use crate::git;
+use crate::languages::{self, CommentLexer, CommentSpan, CommentStyle, LexedLine};
+use crate::models::{CodeMarkerConfig, CodeMarkerScope, Config, DebtMarker, MarkerKind};
 use anyhow::{Context, Result};
+use git2::{ObjectType, Oid, Repository, Tree, TreeWalkMode, TreeWalkResult};
 use ignore::WalkBuilder;
+use rayon::prelude::*;
 use regex::Regex;
 use std::collections::VecDeque;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::path::Path;
-use std::sync::{Arc, Mutex};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
 
 const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024; // 10MB
 
+/// Matches issue references: URLs, tracker keys like `JIRA-456`, and `#123`
+static ISSUE_REF_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"https?://[^\s)>\]]+|\b[A-Z][A-Z0-9]+-\d+\b|(?:^|[^\w&])(#\d+)\b")
+        .expect("valid issue reference regex")
+});
+
 /// Scan a directory for technical debt markers
 pub fn scan_directory(path: &Path, config: &Config) -> Result<Vec<DebtMarker>> {
+    let markers = Mutex::new(Vec::new());
+    scan_directory_each(path, config, |file_markers| {
+        if let Ok(mut markers) = markers.lock() {
+            markers.extend(file_markers);
+        }
+    })?;
+
+    markers
+        .into_inner()
+        .map_err(|_| anyhow::anyhow!("Failed to extract markers"))
+}
+
+/// Scan a directory, passing each file's markers to `on_file` as soon as the
+/// file has been scanned
+///
+/// Markers get fingerprints and severities as in [`scan_directory`], but
+/// aren't collected, so memory use doesn't grow with the number of markers.
+/// `on_file` is called from the walker's threads and only for files with
+/// markers.
+pub fn scan_directory_each<F>(path: &Path, config: &Config, on_file: F) -> Result<()>
+where
+    F: Fn(Vec<DebtMarker>) + Sync,
+{
     // Build regex pattern from config markers
-    let pattern = Arc::new(build_marker_regex(&config.markers)?);
+    let pattern = build_marker_regex(&config.markers)?;
+    let code_markers = CodeMarkerRegistry::new(&config.code_markers)?;
     let context_lines = config.context_lines;
-
-    // Thread-safe vector to collect markers
-    let markers = Arc::new(Mutex::new(Vec::new()));
+    // Fingerprints use paths relative to the repository root
+    let prefix = git::repo_prefix(path);
 
     // Build the file walker
     let mut walker = WalkBuilder::new(path);
@@ -32,8 +66,7 @@ pub fn scan_directory(path: &Path, config: &Config) -> Result<Vec<DebtMarker>> {
 
     // Walk the directory tree in parallel
     walker.build_parallel().run(|| {
-        let pattern = Arc::clone(&pattern);
-        let markers = Arc::clone(&markers);
+        let (pattern, code_markers, prefix, on_file) = (&pattern, &code_markers, &prefix, &on_file);
 
         Box::new(move |result| {
             use ignore::WalkState;
@@ -49,57 +82,407 @@ pub fn scan_directory(path: &Path, config: &Config) -> Result<Vec<DebtMarker>> {
             }
 
             // Skip if file is too large
-            if let Ok(metadata) = entry.metadata() {
-                if metadata.len() > MAX_FILE_SIZE {
-                    return WalkState::Continue;
-                }
+            if let Ok(metadata) = entry.metadata()
+                && metadata.len() > MAX_FILE_SIZE
+            {
+                return WalkState::Continue;
             }
 
             // Scan the file for markers
-            if let Ok(file_markers) = scan_file(entry.path(), &pattern, context_lines) {
-                if !file_markers.is_empty() {
-                    if let Ok(mut markers) = markers.lock() {
-                        markers.extend(file_markers);
-                    }
-                }
+            if let Ok(mut file_markers) =
+                scan_file(entry.path(), pattern, code_markers, context_lines)
+                && !file_markers.is_empty()
+            {
+                fingerprint::assign_fingerprints(&mut file_markers, path, prefix);
+                assign_severities(&mut file_markers, config);
+                on_file(file_markers);
             }
 
             WalkState::Continue
         })
     });
 
-    // Extract the markers from the Arc<Mutex<>>
-    let markers = Arc::try_unwrap(markers)
-        .map_err(|_| anyhow::anyhow!("Failed to unwrap markers"))?
-        .into_inner()
-        .map_err(|_| anyhow::anyhow!("Failed to extract markers"))?;
+    Ok(())
+}
+
+/// Scan a git tree for technical debt markers without checking it out
+///
+/// Files are read straight from the object database, so any commit can be
+/// scanned. Marker paths are `base` joined with the path inside `tree`, and
+/// `prefix` is the tree's location in the repository (used for fingerprints).
+/// Like [`scan_directory`], hidden entries, `ignored_dirs` and files over the
+/// size limit are skipped.
+pub fn scan_tree(
+    repo: &Repository,
+    tree: &Tree,
+    prefix: &Path,
+    base: &Path,
+    config: &Config,
+) -> Result<Vec<DebtMarker>> {
+    let pattern = build_marker_regex(&config.markers)?;
+    let code_markers = CodeMarkerRegistry::new(&config.code_markers)?;
+
+    let mut blobs: Vec<(PathBuf, Oid)> = Vec::new();
+    tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+        let name = entry.name().unwrap_or("");
+        if name.starts_with('.') || config.ignored_dirs.iter().any(|ignored| ignored == name) {
+            return TreeWalkResult::Skip;
+        }
+        if entry.kind() == Some(ObjectType::Blob) {
+            blobs.push((Path::new(root).join(name), entry.id()));
+        }
+        TreeWalkResult::Ok
+    })
+    .context("Failed to walk git tree")?;
+
+    // git2 objects can't be shared across threads, so each worker opens its
+    // own handle to the repository
+    let repo_path = repo.path().to_path_buf();
+    let mut markers: Vec<DebtMarker> = blobs
+        .par_iter()
+        .map_init(
+            || Repository::open(&repo_path).ok(),
+            |worker_repo, (tree_path, blob_id)| {
+                let Some(blob) = worker_repo
+                    .as_ref()
+                    .and_then(|r| r.find_blob(*blob_id).ok())
+                else {
+                    return Vec::new();
+                };
+                if blob.size() as u64 > MAX_FILE_SIZE {
+                    return Vec::new();
+                }
+                let mut markers = scan_reader(
+                    &base.join(tree_path),
+                    blob.content(),
+                    &pattern,
+                    &code_markers,
+                    config.context_lines,
+                );
+                for marker in &mut markers {
+                    marker.fingerprint = fingerprint::fingerprint(marker, &prefix.join(tree_path));
+                }
+                markers
+            },
+        )
+        .flatten()
+        .collect();
+
+    assign_severities(&mut markers, config);
 
     Ok(markers)
 }
 
-/// Build regex pattern to match debt markers in comments
+/// Set each marker's severity from the `[severity]` mapping
+fn assign_severities(markers: &mut [DebtMarker], config: &Config) {
+    for marker in markers {
+        marker.severity = config.severity_of(&marker.marker_type);
+    }
+}
+
+/// Build regex pattern to match debt markers at the start of comment text
 fn build_marker_regex(markers: &[String]) -> Result<Regex> {
     let markers_pattern = markers.join("|");
 
-    // Match common comment styles with the markers
-    // Handles: //, #, /*, *, <!--
+    // Comment text may start with decoration left over from the comment
+    // leader, e.g. `///`, `/**`, ` * `, `;;;` or `---`
+    // Groups: 1 = marker, 2/3 = `(assignee)` / `[assignee]`, 4 = description
     let pattern = format!(
-        r"^\s*(?://|#|/\*|\*|<!--)\s*({})(?::|\s)?\s*(.*?)(?:-->|\*/)?$",
+        r"^[\s/*!#;%-]*({})\b(?:\(([^)]*)\)|\[([^\]]*)\])?(?::|\s)?\s*(.*?)\s*$",
         markers_pattern
     );
 
     Regex::new(&pattern).context("Failed to compile marker regex")
 }
 
+/// How the comment holding a marker may continue onto following lines
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Continuation {
+    /// Line comment: continuation lines are whole-line comments with the same
+    /// leader and decoration (so `///` docs don't run into `//` comments)
+    Line {
+        leader: &'static str,
+        decoration: Option<char>,
+    },
+    /// Block comment: lines belong to the note until the block closes
+    Block,
+}
+
+impl Continuation {
+    /// Determine how the comment a marker was found in may continue. Returns
+    /// `None` when a block comment already closes on the marker line.
+    fn for_span(span: &CommentSpan) -> Option<Self> {
+        match span.style {
+            CommentStyle::Line(leader) => Some(Continuation::Line {
+                leader,
+                decoration: line_decoration(span.text, leader),
+            }),
+            CommentStyle::Block(_) if span.open_at_end => Some(Continuation::Block),
+            CommentStyle::Block(_) => None,
+        }
+    }
+
+    /// Extract the note text from the comments on a continuation line
+    ///
+    /// Returns the text and whether the comment closes on this line, or `None`
+    /// if the line does not continue the note (code, a blank comment line, or a
+    /// different comment style).
+    fn continue_with<'a>(&self, comments: &[CommentSpan<'a>]) -> Option<(&'a str, bool)> {
+        let span = comments.first()?;
+
+        let (content, closed) = match *self {
+            Continuation::Line { leader, decoration } => {
+                if span.trailing
+                    || span.style != CommentStyle::Line(leader)
+                    || line_decoration(span.text, leader) != decoration
+                {
+                    return None;
+                }
+                let text = span.text.trim_start_matches(|c| is_decoration(c, leader));
+                (text, false)
+            }
+            Continuation::Block => {
+                if !span.continued {
+                    return None;
+                }
+                // Doc blocks commonly prefix each line with ` * `
+                let text = span.text.trim_start();
+                (text.strip_prefix('*').unwrap_or(text), !span.open_at_end)
+            }
+        };
+
+        let content = content.trim();
+        if content.is_empty() {
+            None
+        } else {
+            Some((content, closed))
+        }
+    }
+}
+
+/// Characters directly after a line comment leader that mark a doc comment
+/// style, e.g. the third `/` of `///` or the `!` of `//!`
+fn is_decoration(c: char, leader: &str) -> bool {
+    leader.contains(c) || c == '!'
+}
+
+/// The decoration character a line comment starts with, if any
+fn line_decoration(text: &str, leader: &str) -> Option<char> {
+    text.chars().next().filter(|&c| is_decoration(c, leader))
+}
+
+/// A compiled code marker pattern
+#[derive(Debug)]
+struct CodePattern {
+    name: String,
+    regex: Regex,
+    scope: CodeMarkerScope,
+    extensions: Vec<String>,
+}
+
+impl CodePattern {
+    fn applies_to(&self, extension: &str) -> bool {
+        self.extensions.is_empty()
+            || self
+                .extensions
+                .iter()
+                .any(|ext| ext.eq_ignore_ascii_case(extension))
+    }
+
+    /// Match the pattern against a line, returning the marker description
+    fn find(&self, line: &str, lexed: &LexedLine) -> Option<String> {
+        let comments = &lexed.comments;
+        match self.scope {
+            CodeMarkerScope::Code => {
+                // A line that starts inside a block comment holds no code we can trust
+                if comments.first().is_some_and(|span| span.continued) {
+                    return None;
+                }
+                let code_end = comments.first().map_or(line.len(), |span| span.start);
+                let captures = self
+                    .regex
+                    .captures_iter(&line[..code_end])
+                    .find(|captures| {
+                        captures.get(0).is_some_and(|m| !lexed.in_string(m.start()))
+                    })?;
+
+                // A comment on the same line is the justification, e.g.
+                // `#[allow(dead_code)] // used from FFI`
+                let detail = captures.get(1).map(|m| m.as_str().trim());
+                let justification = comments.first().map(|span| span.text.trim());
+                let parts: Vec<&str> = [detail, justification]
+                    .into_iter()
+                    .flatten()
+                    .filter(|part| !part.is_empty())
+                    .collect();
+                Some(parts.join(": "))
+            }
+            CodeMarkerScope::Comment => comments.iter().find_map(|span| {
+                let captures = self.regex.captures(span.text)?;
+                let description = match captures.get(1) {
+                    Some(detail) => detail.as_str(),
+                    None => &span.text[captures.get(0).map_or(0, |m| m.end())..],
+                };
+                Some(description.trim_start_matches(':').trim().to_string())
+            }),
+        }
+    }
+}
+
+/// Registry of code-level debt patterns (`todo!()`, `# noqa`, `@Deprecated`...),
+/// matched separately from the comment marker regex
+#[derive(Debug, Default)]
+pub struct CodeMarkerRegistry {
+    patterns: Vec<CodePattern>,
+}
+
+impl CodeMarkerRegistry {
+    /// Compile the code markers from the configuration
+    pub fn new(code_markers: &[CodeMarkerConfig]) -> Result<Self> {
+        let patterns = code_markers
+            .iter()
+            .map(|marker| {
+                let regex = Regex::new(&marker.pattern)
+                    .with_context(|| format!("Invalid pattern for code marker {}", marker.name))?;
+                Ok(CodePattern {
+                    name: marker.name.clone(),
+                    regex,
+                    scope: marker.scope,
+                    extensions: marker.extensions.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { patterns })
+    }
+
+    /// Patterns that apply to the given file
+    fn for_path(&self, path: &Path) -> Vec<&CodePattern> {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        self.patterns
+            .iter()
+            .filter(|pattern| pattern.applies_to(extension))
+            .collect()
+    }
+}
+
+/// A marker detected on a line, before context is attached
+struct DetectedMarker {
+    marker_type: String,
+    kind: MarkerKind,
+    description: String,
+    assignee: Option<String>,
+    continuation: Option<Continuation>,
+}
+
+/// Find the marker on a line, if any: comment markers take precedence over
+/// code markers
+fn detect_marker(
+    line: &str,
+    lexed: &LexedLine,
+    pattern: &Regex,
+    code_patterns: &[&CodePattern],
+) -> Option<DetectedMarker> {
+    let comment_marker = lexed.comments.iter().find_map(|span| {
+        let captures = pattern.captures(span.text)?;
+        Some(DetectedMarker {
+            marker_type: captures
+                .get(1)
+                .map(|m| m.as_str().to_string())
+                .unwrap_or_default(),
+            kind: MarkerKind::Comment,
+            description: captures
+                .get(4)
+                .map(|m| m.as_str().trim().to_string())
+                .unwrap_or_default(),
+            assignee: captures
+                .get(2)
+                .or_else(|| captures.get(3))
+                .map(|m| m.as_str().trim().to_string())
+                .filter(|name| !name.is_empty()),
+            continuation: Continuation::for_span(span),
+        })
+    });
+
+    comment_marker.or_else(|| {
+        code_patterns.iter().find_map(|code_pattern| {
+            let description = code_pattern.find(line, lexed)?;
+            Some(DetectedMarker {
+                marker_type: code_pattern.name.clone(),
+                kind: MarkerKind::Code,
+                description,
+                assignee: None,
+                continuation: None,
+            })
+        })
+    })
+}
+
+/// A marker whose body and trailing context are still being collected
+struct PendingMarker {
+    marker: DebtMarker,
+    continuation: Option<Continuation>,
+    context_remaining: usize,
+}
+
+impl PendingMarker {
+    fn is_complete(&self) -> bool {
+        self.continuation.is_none() && self.context_remaining == 0
+    }
+}
+
+/// Collect issue references (`#123`, `JIRA-456`, URLs) from a marker description
+fn extract_issue_refs(description: &str) -> Vec<String> {
+    let mut issue_refs: Vec<String> = Vec::new();
+
+    for captures in ISSUE_REF_REGEX.captures_iter(description) {
+        // `#123` is captured in group 1 so the preceding character is dropped
+        let reference = captures
+            .get(1)
+            .or_else(|| captures.get(0))
+            .map(|m| m.as_str().to_string())
+            .unwrap_or_default();
+        if !issue_refs.contains(&reference) {
+            issue_refs.push(reference);
+        }
+    }
+
+    issue_refs
+}
+
 /// Scan a single file for debt markers
-fn scan_file(path: &Path, pattern: &Regex, context_lines: usize) -> Result<Vec<DebtMarker>> {
+fn scan_file(
+    path: &Path,
+    pattern: &Regex,
+    code_markers: &CodeMarkerRegistry,
+    context_lines: usize,
+) -> Result<Vec<DebtMarker>> {
     let file =
         File::open(path).with_context(|| format!("Failed to open file: {}", path.display()))?;
-    let mut reader = BufReader::new(file);
 
+    Ok(scan_reader(
+        path,
+        BufReader::new(file),
+        pattern,
+        code_markers,
+        context_lines,
+    ))
+}
+
+/// Scan file contents for debt markers; `path` selects the comment syntax
+/// and is recorded on each marker
+fn scan_reader<R: BufRead>(
+    path: &Path,
+    mut reader: R,
+    pattern: &Regex,
+    code_markers: &CodeMarkerRegistry,
+    context_lines: usize,
+) -> Vec<DebtMarker> {
     let mut markers = Vec::new();
     let mut line_buffer: VecDeque<String> = VecDeque::new();
-    let mut lines_after_marker: Option<(DebtMarker, usize)> = None;
+    let mut pending: Option<PendingMarker> = None;
+    let mut lexer = CommentLexer::new(languages::syntax_for_path(path));
+    let code_patterns = code_markers.for_path(path);
 
     // Reusable byte buffer
     let mut byte_buffer = Vec::with_capacity(256);
@@ -121,41 +504,83 @@ fn scan_file(path: &Path, pattern: &Regex, context_lines: usize) -> Result<Vec<D
             Err(_) => continue, // Skip non-UTF-8 lines (binary data)
         };
 
-        // If we're collecting context after a marker
-        if let Some((mut marker, remaining)) = lines_after_marker.take() {
-            if remaining > 0 {
-                marker.context_after.push(line.to_string());
-                lines_after_marker = Some((marker, remaining - 1));
+        // Only text inside real comments can hold a marker
+        let lexed = lexer.lex(line);
+        let found = detect_marker(line, &lexed, pattern, &code_patterns);
+
+        if let Some(mut current) = pending.take() {
+            // Continuation lines of the same comment extend the marker body
+            let mut is_body_line = false;
+            if let Some(continuation) = current.continuation {
+                match continuation
+                    .continue_with(&lexed.comments)
+                    .filter(|_| found.is_none())
+                {
+                    Some((text, closed)) => {
+                        let marker = &mut current.marker;
+                        marker.continuation_lines.push(line.to_string());
+                        if !marker.description.is_empty() {
+                            marker.description.push(' ');
+                        }
+                        marker.description.push_str(text);
+                        marker.issue_refs = extract_issue_refs(&marker.description);
+                        if closed {
+                            current.continuation = None;
+                        }
+                        is_body_line = true;
+                    }
+                    None => current.continuation = None,
+                }
+            }
+
+            // Otherwise we're collecting context after the marker
+            if !is_body_line && current.context_remaining > 0 {
+                current.marker.context_after.push(line.to_string());
+                current.context_remaining -= 1;
+            }
+
+            if current.is_complete() || found.is_some() {
+                markers.push(current.marker);
             } else {
-                markers.push(marker);
+                pending = Some(current);
             }
         }
 
         // Check if this line contains a marker
-        if let Some(captures) = pattern.captures(line) {
-            let marker_type = captures
-                .get(1)
-                .map(|m| m.as_str().to_string())
-                .unwrap_or_default();
+        if let Some(detected) = found {
+            let issue_refs = extract_issue_refs(&detected.description);
 
             // Extract context before (from buffer)
             let context_before: Vec<String> = line_buffer.iter().cloned().collect();
 
             let marker = DebtMarker {
-                marker_type,
+                marker_type: detected.marker_type,
+                kind: detected.kind,
+                severity: Default::default(), // Assigned from config by the caller
                 file_path: path.to_path_buf(),
                 line_number,
                 line_content: line.to_string(),
+                description: detected.description,
+                assignee: detected.assignee,
+                issue_refs,
+                continuation_lines: Vec::new(),
                 context_before,
                 context_after: Vec::new(),
                 git_info: None, // Will be filled in by git module
+                fingerprint: String::new(),
+                score: 0.0,
             };
 
-            // Start collecting context after
-            if context_lines > 0 {
-                lines_after_marker = Some((marker, context_lines));
+            // Start collecting the body and context after
+            let current = PendingMarker {
+                marker,
+                continuation: detected.continuation,
+                context_remaining: context_lines,
+            };
+            if current.is_complete() {
+                markers.push(current.marker);
             } else {
-                markers.push(marker);
+                pending = Some(current);
             }
 
             // Clear buffer to avoid including marker line in next context
@@ -173,11 +598,11 @@ fn scan_file(path: &Path, pattern: &Regex, context_lines: usize) -> Result<Vec<D
     }
 
     // Don't forget the last marker if we were still collecting context
-    if let Some((marker, _)) = lines_after_marker {
-        markers.push(marker);
+    if let Some(current) = pending {
+        markers.push(current.marker);
     }
 
-    Ok(markers)
+    markers
 }
 
 /// Check if a file is likely binary
@@ -209,6 +634,9 @@ pub fn is_likely_binary(path: &Path) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::Severity;
+    use std::collections::HashMap;
+    use std::fs;
     use std::io::Write;
     use tempfile::TempDir;
 
@@ -242,7 +670,7 @@ fn main() {
 
         let markers = vec!["TODO".to_string(), "FIXME".to_string()];
         let pattern = build_marker_regex(&markers).unwrap();
-        let found = scan_file(&file_path, &pattern, 1).unwrap();
+        let found = scan_file(&file_path, &pattern, &CodeMarkerRegistry::default(), 1).unwrap();
 
         assert_eq!(found.len(), 2);
         assert_eq!(found[0].marker_type, "TODO");
@@ -267,7 +695,7 @@ line 5"#;
 
         let markers = vec!["TODO".to_string()];
         let pattern = build_marker_regex(&markers).unwrap();
-        let found = scan_file(&file_path, &pattern, 2).unwrap();
+        let found = scan_file(&file_path, &pattern, &CodeMarkerRegistry::default(), 2).unwrap();
 
         assert_eq!(found.len(), 1);
         assert_eq!(found[0].context_before.len(), 2);
@@ -278,6 +706,313 @@ line 5"#;
         assert_eq!(found[0].context_after[1], "line 5");
     }
 
+    #[test]
+    fn test_extract_issue_refs() {
+        assert_eq!(extract_issue_refs("handle retries, see #123"), vec!["#123"]);
+        assert_eq!(
+            extract_issue_refs("tracked in JIRA-456 and https://example.com/i/7"),
+            vec!["JIRA-456", "https://example.com/i/7"]
+        );
+        assert!(extract_issue_refs("plain description").is_empty());
+        assert!(extract_issue_refs("escape &#39; is not an issue").is_empty());
+    }
+
+    #[test]
+    fn test_scan_file_extracts_structured_fields() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.rs");
+
+        let content =
+            "// TODO(alice): fix the parser (#42)\n// FIXME[bob] broken\n// NOTE: (not) assigned\n";
+        let mut file = File::create(&file_path).unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+
+        let markers = vec!["TODO".to_string(), "FIXME".to_string(), "NOTE".to_string()];
+        let pattern = build_marker_regex(&markers).unwrap();
+        let found = scan_file(&file_path, &pattern, &CodeMarkerRegistry::default(), 0).unwrap();
+
+        assert_eq!(found.len(), 3);
+        assert_eq!(found[0].assignee.as_deref(), Some("alice"));
+        assert_eq!(found[0].description, "fix the parser (#42)");
+        assert_eq!(found[0].issue_refs, vec!["#42"]);
+        assert_eq!(found[1].assignee.as_deref(), Some("bob"));
+        assert_eq!(found[1].description, "broken");
+        assert_eq!(found[2].assignee, None);
+        assert_eq!(found[2].description, "(not) assigned");
+    }
+
+    #[test]
+    fn test_multiline_line_comments() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.rs");
+
+        let content = r#"fn main() {
+    // TODO: retry the request
+    // with exponential backoff,
+    // see #12
+    retry();
+    // FIXME: one line
+    //
+    // unrelated comment
+}"#;
+
+        let mut file = File::create(&file_path).unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+
+        let markers = vec!["TODO".to_string(), "FIXME".to_string()];
+        let pattern = build_marker_regex(&markers).unwrap();
+        let found = scan_file(&file_path, &pattern, &CodeMarkerRegistry::default(), 1).unwrap();
+
+        assert_eq!(found.len(), 2);
+        assert_eq!(
+            found[0].description,
+            "retry the request with exponential backoff, see #12"
+        );
+        assert_eq!(found[0].continuation_lines.len(), 2);
+        assert_eq!(found[0].issue_refs, vec!["#12"]);
+        assert_eq!(found[0].context_after, vec!["    retry();"]);
+
+        assert_eq!(found[1].description, "one line");
+        assert!(found[1].continuation_lines.is_empty());
+        assert_eq!(found[1].context_after, vec!["    //"]);
+    }
+
+    #[test]
+    fn test_multiline_block_comments_and_docstrings() {
+        let temp_dir = TempDir::new().unwrap();
+        let markers = vec!["TODO".to_string(), "FIXME".to_string()];
+        let pattern = build_marker_regex(&markers).unwrap();
+
+        let c_path = temp_dir.path().join("test.c");
+        let mut file = File::create(&c_path).unwrap();
+        file.write_all(b"/* TODO: first part\n * second part */\ncode();\n")
+            .unwrap();
+
+        let found = scan_file(&c_path, &pattern, &CodeMarkerRegistry::default(), 0).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].description, "first part second part");
+
+        let py_path = temp_dir.path().join("test.py");
+        let content = r#"def f():
+    """FIXME: docstring note
+    continues here
+
+    Args: ignored
+    """
+"#;
+        let mut file = File::create(&py_path).unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+
+        let found = scan_file(&py_path, &pattern, &CodeMarkerRegistry::default(), 0).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].line_number, 2);
+        assert_eq!(found[0].description, "docstring note continues here");
+    }
+
+    #[test]
+    fn test_adjacent_markers_are_kept() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.rs");
+
+        let mut file = File::create(&file_path).unwrap();
+        file.write_all(b"// TODO: one\n// FIXME: two\n").unwrap();
+
+        let markers = vec!["TODO".to_string(), "FIXME".to_string()];
+        let pattern = build_marker_regex(&markers).unwrap();
+        let found = scan_file(&file_path, &pattern, &CodeMarkerRegistry::default(), 2).unwrap();
+
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].description, "one");
+        assert_eq!(found[1].description, "two");
+    }
+
+    #[test]
+    fn test_markers_only_in_real_comments() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.rs");
+
+        let content = r#"#[allow(dead_code)]
+fn main() {
+    let s = "// TODO: inside a string";
+    call(); // FIXME: trailing comment
+    /// TODO: doc comment
+    // see the TODO list
+}"#;
+
+        let mut file = File::create(&file_path).unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+
+        let markers = vec!["TODO".to_string(), "FIXME".to_string()];
+        let pattern = build_marker_regex(&markers).unwrap();
+        let found = scan_file(&file_path, &pattern, &CodeMarkerRegistry::default(), 0).unwrap();
+
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].marker_type, "FIXME");
+        assert_eq!(found[0].line_number, 4);
+        assert_eq!(found[0].description, "trailing comment");
+        assert_eq!(found[1].marker_type, "TODO");
+        assert_eq!(found[1].description, "doc comment");
+    }
+
+    #[test]
+    fn test_language_specific_comments() {
+        let temp_dir = TempDir::new().unwrap();
+        let markers = vec!["TODO".to_string()];
+        let pattern = build_marker_regex(&markers).unwrap();
+
+        let cases = [
+            ("query.sql", "SELECT 1; -- TODO: index this"),
+            ("init.lua", "local x = 1 -- TODO: index this"),
+            ("core.el", "(setq x 1) ; TODO: index this"),
+            ("Main.hs", "{- TODO: index this -}"),
+            ("app.erl", "% TODO: index this"),
+            ("paper.tex", "Some text % TODO: index this"),
+        ];
+
+        for (name, content) in cases {
+            let file_path = temp_dir.path().join(name);
+            let mut file = File::create(&file_path).unwrap();
+            file.write_all(content.as_bytes()).unwrap();
+
+            let found = scan_file(&file_path, &pattern, &CodeMarkerRegistry::default(), 0).unwrap();
+            assert_eq!(found.len(), 1, "no marker found in {}", name);
+            assert_eq!(found[0].description, "index this", "in {}", name);
+        }
+    }
+
+    #[test]
+    fn test_code_markers() {
+        let temp_dir = TempDir::new().unwrap();
+        let markers = vec!["TODO".to_string()];
+        let pattern = build_marker_regex(&markers).unwrap();
+        let registry = CodeMarkerRegistry::new(&Config::default().code_markers).unwrap();
+
+        let rs_path = temp_dir.path().join("lib.rs");
+        let content = r#"#[allow(dead_code)] // used from FFI
+fn a() { todo!("write a") }
+fn b() { unimplemented!() }
+// todo!() in a comment is not code
+const S: &str = "todo!() in a string is not code";
+fn c() { todo!() } // TODO: comment marker wins
+"#;
+        let mut file = File::create(&rs_path).unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+
+        let found = scan_file(&rs_path, &pattern, &registry, 0).unwrap();
+        let summary: Vec<_> = found
+            .iter()
+            .map(|m| (m.marker_type.as_str(), m.kind, m.description.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("#[allow]", MarkerKind::Code, "dead_code: used from FFI"),
+                ("todo!()", MarkerKind::Code, "write a"),
+                ("unimplemented!()", MarkerKind::Code, ""),
+                ("TODO", MarkerKind::Comment, "comment marker wins"),
+            ]
+        );
+
+        let py_path = temp_dir.path().join("test_app.py");
+        let content = r##"import os  # noqa: F401
+x: int = "a"  # type: ignore[assignment]
+@pytest.mark.skip(reason="flaky")
+def test_x(): pass
+s = "# noqa"
+"##;
+        let mut file = File::create(&py_path).unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+
+        let found = scan_file(&py_path, &pattern, &registry, 0).unwrap();
+        let summary: Vec<_> = found
+            .iter()
+            .map(|m| (m.marker_type.as_str(), m.description.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("noqa", "F401"),
+                ("type: ignore", "assignment"),
+                ("pytest.mark.skip", "reason=\"flaky\""),
+            ]
+        );
+
+        let ts_path = temp_dir.path().join("app.ts");
+        let mut file = File::create(&ts_path).unwrap();
+        file.write_all(b"// @ts-ignore: legacy types\n/* eslint-disable no-console */\n")
+            .unwrap();
+
+        let found = scan_file(&ts_path, &pattern, &registry, 0).unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].marker_type, "@ts-ignore");
+        assert_eq!(found[0].description, "legacy types");
+        assert_eq!(found[1].marker_type, "eslint-disable");
+        assert_eq!(found[1].description, "no-console");
+    }
+
+    #[test]
+    fn test_invalid_code_marker_pattern() {
+        let config = CodeMarkerConfig {
+            name: "broken".to_string(),
+            pattern: "(".to_string(),
+            scope: CodeMarkerScope::Code,
+            extensions: vec![],
+        };
+        assert!(CodeMarkerRegistry::new(&[config]).is_err());
+    }
+
+    #[test]
+    fn test_scan_tree_reads_committed_blobs() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(root)
+                .output()
+                .unwrap();
+        };
+
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("node_modules")).unwrap();
+        fs::write(root.join("src/a.rs"), "fn a() {}\n// TODO: committed\n").unwrap();
+        fs::write(root.join("node_modules/x.js"), "// TODO: ignored dir\n").unwrap();
+        fs::write(root.join(".hidden.rs"), "// TODO: hidden\n").unwrap();
+        git(&["init"]);
+        git(&["add", "-A"]);
+        git(&[
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "-m",
+            "Initial",
+        ]);
+
+        // Uncommitted changes are not seen
+        fs::write(root.join("src/a.rs"), "// FIXME: uncommitted\n").unwrap();
+
+        let repo = Repository::open(root).unwrap();
+        let tree = repo.head().unwrap().peel_to_tree().unwrap();
+        let config = Config {
+            severity: HashMap::from([("TODO".to_string(), Severity::Low)]),
+            ..Config::default()
+        };
+        let markers = scan_tree(&repo, &tree, Path::new(""), Path::new("."), &config).unwrap();
+
+        assert_eq!(markers.len(), 1);
+        assert_eq!(markers[0].marker_type, "TODO");
+        assert_eq!(markers[0].description, "committed");
+        assert_eq!(markers[0].file_path, Path::new("./src/a.rs"));
+        assert_eq!(markers[0].line_number, 2);
+        assert_eq!(markers[0].severity, Severity::Low);
+        assert_eq!(
+            markers[0].fingerprint,
+            fingerprint::fingerprint(&markers[0], Path::new("src/a.rs"))
+        );
+    }
+
     #[test]
     fn test_is_likely_binary() {
         assert!(is_likely_binary(Path::new("image.png")));
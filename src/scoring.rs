@@ -0,0 +1,132 @@
+use crate::filters;
+use crate::fingerprint;
+use crate::models::{DebtMarker, ScoringConfig};
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Score a marker
+///
+/// The score is the severity weight times the age factor
+/// `1 + age_days / age_scale_days` (1 without git information), times the
+/// number of recent commits to the file when `churn` is given (at least 1, so
+/// untouched files aren't scored zero). Scores are rounded to two decimals.
+pub fn score_marker(
+    marker: &DebtMarker,
+    config: &ScoringConfig,
+    age_scale_days: f64,
+    churn: Option<usize>,
+) -> f64 {
+    let age_days = marker
+        .git_info
+        .as_ref()
+        .map_or(0, |info| info.age_days.max(0));
+    let age_factor = 1.0 + age_days as f64 / age_scale_days;
+    let churn_factor = churn.map_or(1, |commits| commits.max(1));
+
+    let score = config.weight(marker.severity) * age_factor * churn_factor as f64;
+    (score * 100.0).round() / 100.0
+}
+
+/// Fill in `score` for markers found under `scan_path`
+///
+/// `churn` maps files (relative to the repository root; `prefix` locates
+/// `scan_path` within it) to their recent commit counts; pass `None` to leave
+/// churn out of the score.
+pub fn score_markers(
+    markers: &mut [DebtMarker],
+    config: &ScoringConfig,
+    churn: Option<&HashMap<PathBuf, usize>>,
+    scan_path: &Path,
+    prefix: &Path,
+) -> Result<()> {
+    let age_scale_days = filters::parse_duration(&config.age_scale)
+        .with_context(|| format!("Invalid scoring age_scale: {}", config.age_scale))?
+        .as_secs()
+        / (24 * 60 * 60);
+    // A zero scale would make every age infinitely bad
+    let age_scale_days = age_scale_days.max(1) as f64;
+
+    for marker in markers {
+        let commits = churn.map(|counts| {
+            let path = fingerprint::repo_path(marker, scan_path, prefix);
+            counts.get(&path).copied().unwrap_or(0)
+        });
+        marker.score = score_marker(marker, config, age_scale_days, commits);
+    }
+
+    Ok(())
+}
+
+/// Filter markers by minimum score
+pub fn filter_by_min_score(markers: Vec<DebtMarker>, min_score: f64) -> Vec<DebtMarker> {
+    markers
+        .into_iter()
+        .filter(|marker| marker.score >= min_score)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{DebtReport, GitBlameInfo, Severity};
+
+    fn marker(file: &str, severity: Severity, age_days: Option<i64>) -> DebtMarker {
+        DebtMarker {
+            severity,
+            git_info: age_days.map(|age_days| GitBlameInfo::test("Alice", age_days)),
+            ..DebtMarker::test(file, 1, "TODO")
+        }
+    }
+
+    #[test]
+    fn test_score_marker() {
+        let config = ScoringConfig::default();
+
+        // A 3-year-old FIXME outweighs a 5-day-old NOTE
+        let old_fixme = marker("a.rs", Severity::High, Some(3 * 365));
+        let new_note = marker("a.rs", Severity::Info, Some(5));
+        assert_eq!(score_marker(&old_fixme, &config, 365.0, None), 16.0);
+        assert_eq!(score_marker(&new_note, &config, 365.0, None), 0.51);
+
+        // No git information means no age factor
+        let unknown = marker("a.rs", Severity::Medium, None);
+        assert_eq!(score_marker(&unknown, &config, 365.0, None), 2.0);
+
+        // Churn multiplies, but never zeroes, the score
+        assert_eq!(score_marker(&unknown, &config, 365.0, Some(5)), 10.0);
+        assert_eq!(score_marker(&unknown, &config, 365.0, Some(0)), 2.0);
+    }
+
+    #[test]
+    fn test_score_markers_and_report_totals() {
+        let mut config = ScoringConfig::default();
+        config.weights.insert(Severity::Medium, 3.0);
+
+        let mut markers = vec![
+            marker("./src/a.rs", Severity::Medium, Some(0)),
+            marker("./src/a.rs", Severity::High, Some(365)),
+            marker("./README.md", Severity::Low, None),
+        ];
+        let churn = HashMap::from([(PathBuf::from("src/a.rs"), 2)]);
+        score_markers(
+            &mut markers,
+            &config,
+            Some(&churn),
+            Path::new("."),
+            Path::new(""),
+        )
+        .unwrap();
+        let scores: Vec<f64> = markers.iter().map(|m| m.score).collect();
+        assert_eq!(scores, vec![6.0, 16.0, 1.0]);
+
+        let report = DebtReport::new(markers.clone(), PathBuf::from("."));
+        assert_eq!(report.debt_index, 23.0);
+        assert_eq!(report.score_by_file[Path::new("./src/a.rs")], 22.0);
+        assert_eq!(report.score_by_dir[Path::new("./src")], 22.0);
+        assert_eq!(report.score_by_dir[Path::new(".")], 1.0);
+        assert_eq!(report.highest_scoring(1)[0].score, 16.0);
+
+        assert_eq!(filter_by_min_score(markers, 5.0).len(), 2);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::git::Blamer;
+use crate::models::{Config, DebtMarker, DebtReport};
+use crate::reporter;
+use crate::scanner;
+use anyhow::{Context, Result, anyhow};
+use git2::Repository;
+use rayon::prelude::*;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Mutex, mpsc};
+use std::thread;
+
+/// Files waiting to be blamed before the scanner blocks, bounding memory use
+const QUEUED_FILES: usize = 64;
+
+/// Scan `path` and write markers as NDJSON as soon as they have been blamed,
+/// followed by a summary record (see [`reporter::ndjson_summary`])
+///
+/// Files are scanned and blamed in parallel, so each file's markers are
+/// written together but files come out in no particular order.
+/// `before_blame` filters each file's markers before blame; it runs on one
+/// thread at a time and may keep state, such as a baseline matcher.
+/// `after_blame` filters and scores them afterwards. Markers are dropped once
+/// written and only the report's totals are kept, so memory use doesn't grow
+/// with the number of markers. Returns the totals, as a report without
+/// markers.
+pub fn stream_ndjson<W, B, A>(
+    path: &Path,
+    config: &Config,
+    repo: Option<&Repository>,
+    mut before_blame: B,
+    after_blame: A,
+    out: W,
+) -> Result<DebtReport>
+where
+    W: Write + Send,
+    B: FnMut(Vec<DebtMarker>) -> Vec<DebtMarker> + Send,
+    A: Fn(Vec<DebtMarker>) -> Result<Vec<DebtMarker>> + Sync,
+{
+    let blamer = repo
+        .map(|repo| Blamer::new(repo, &config.blame))
+        .transpose()?;
+    let pool = match &blamer {
+        Some(blamer) => blamer.pool()?,
+        None => rayon::ThreadPoolBuilder::new()
+            .build()
+            .context("Failed to build thread pool")?,
+    };
+
+    let output = Mutex::new((out, DebtReport::empty(path.to_path_buf())));
+    let (sender, receiver) = mpsc::sync_channel::<Vec<DebtMarker>>(QUEUED_FILES);
+
+    thread::scope(|scope| {
+        let scan = scope.spawn(move || {
+            // Sends only fail once writing has failed, which is reported below
+            scanner::scan_directory_each(path, config, |markers| {
+                let _ = sender.send(markers);
+            })
+        });
+
+        let written = pool.install(|| {
+            receiver
+                .into_iter()
+                .map(&mut before_blame)
+                .filter(|markers| !markers.is_empty())
+                .par_bridge()
+                .try_for_each_init(
+                    || blamer.as_ref().and_then(Blamer::open),
+                    |worker_repo, mut markers| {
+                        if let (Some(blamer), Some(worker_repo)) = (&blamer, worker_repo) {
+                            blamer.enrich_file(worker_repo, &mut markers);
+                        }
+                        let markers = after_blame(markers)?;
+
+                        let mut lines = String::new();
+                        for marker in &markers {
+                            lines.push_str(
+                                &serde_json::to_string(marker)
+                                    .context("Failed to serialize marker to JSON")?,
+                            );
+                            lines.push('\n');
+                        }
+
+                        let mut output = output
+                            .lock()
+                            .map_err(|_| anyhow!("NDJSON output lock poisoned"))?;
+                        let (out, summary) = &mut *output;
+                        out.write_all(lines.as_bytes())
+                            .and_then(|_| out.flush())
+                            .context("Failed to write NDJSON output")?;
+                        for marker in &markers {
+                            summary.count(marker);
+                        }
+                        Ok(())
+                    },
+                )
+        });
+
+        let scanned = scan
+            .join()
+            .map_err(|_| anyhow!("Directory scan panicked"))?;
+        written.and(scanned)
+    })?;
+
+    let (mut out, summary) = output
+        .into_inner()
+        .map_err(|_| anyhow!("NDJSON output lock poisoned"))?;
+    writeln!(out, "{}", reporter::ndjson_summary(&summary)?)
+        .and_then(|_| out.flush())
+        .context("Failed to write NDJSON output")?;
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::BlameConfig;
+    use std::fs;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_stream_ndjson() {
+        let dir = TempDir::new().unwrap();
+        let repo_path = dir.path();
+        let git = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(repo_path)
+                .output()
+                .unwrap()
+        };
+        git(&["init"]);
+        git(&["config", "user.name", "Test User"]);
+        git(&["config", "user.email", "test@example.com"]);
+        for i in 0..20 {
+            fs::write(
+                repo_path.join(format!("f{}.rs", i)),
+                "// TODO: one\nfn a() {}\n// FIXME: two\n// NOTE: three\n",
+            )
+            .unwrap();
+        }
+        git(&["add", "."]);
+        git(&["commit", "-m", "Initial commit"]);
+
+        let config = Config {
+            blame: BlameConfig {
+                cache: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let repo = Repository::open(repo_path).unwrap();
+        let mut out = Vec::new();
+        let summary = stream_ndjson(
+            repo_path,
+            &config,
+            Some(&repo),
+            |markers| {
+                markers
+                    .into_iter()
+                    .filter(|m| m.marker_type != "NOTE")
+                    .collect()
+            },
+            |mut markers| {
+                markers.retain(|m| m.git_info.is_some());
+                Ok(markers)
+            },
+            &mut out,
+        )
+        .unwrap();
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 41);
+        assert!(
+            lines[..40]
+                .iter()
+                .all(|m| m["git_info"]["author"] == "Test User")
+        );
+        assert!(lines[..40].iter().all(|m| m["marker_type"] != "NOTE"));
+
+        let last = &lines[40]["summary"];
+        assert_eq!(last["total_count"], 40);
+        assert_eq!(last["by_type"]["FIXME"], 20);
+        assert!(last.get("markers").is_none());
+        assert_eq!(summary.total_count, 40);
+        assert!(summary.markers.is_empty());
+    }
+}
@@ -0,0 +1,412 @@
+use crate::models::{
+    DebtMarker, DebtReport, GroupKey, MarkerGroup, SortKey, SortOrder, format_age,
+};
+use crate::reporter;
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use tera::{Tera, Value};
+
+/// Prefix selecting a template shipped with fossil rather than a file
+pub const BUILTIN_PREFIX: &str = "builtin:";
+
+/// Templates shipped with fossil, by name
+const BUILTINS: &[(&str, &str)] = &[
+    ("terminal", include_str!("template/terminal.tera")),
+    ("markdown", include_str!("template/markdown.tera")),
+];
+
+/// A count in a group
+#[derive(Serialize)]
+struct GroupCount {
+    name: String,
+    count: usize,
+}
+
+/// A score total for a file or directory
+#[derive(Serialize)]
+struct PathScore {
+    path: String,
+    score: f64,
+}
+
+/// A group from `groups`, flattened for templates that can't recurse
+#[derive(Serialize)]
+struct GroupRow<'a> {
+    depth: usize,
+    key: &'a str,
+    count: usize,
+    score: f64,
+}
+
+/// The top markers list
+#[derive(Serialize)]
+struct TopMarkers<'a> {
+    title: String,
+    markers: Vec<&'a DebtMarker>,
+}
+
+/// Variables available to templates
+///
+/// Group lists are `{name, count}` objects, highest count first (ties by
+/// name); score lists are `{path, score}` objects, highest score first.
+#[derive(Serialize)]
+struct TemplateContext<'a> {
+    /// Scanned path
+    scan_path: String,
+
+    /// Scan time, RFC 3339
+    scan_time: DateTime<Utc>,
+
+    /// Scan time as `YYYY-MM-DD HH:MM:SS UTC`
+    generated: String,
+
+    /// Number of markers
+    total_count: usize,
+
+    /// Sum of marker scores
+    debt_index: f64,
+
+    /// All markers, with the same fields as JSON output
+    markers: &'a [DebtMarker],
+
+    /// `title` and `markers` of the `--top`/`--sort-by` list
+    top: TopMarkers<'a>,
+
+    by_type: Vec<GroupCount>,
+    by_author: Vec<GroupCount>,
+    by_file: Vec<GroupCount>,
+
+    /// Severity counts, most severe first
+    by_severity: Vec<GroupCount>,
+
+    score_by_file: Vec<PathScore>,
+    score_by_dir: Vec<PathScore>,
+
+    /// Headings of the `--group-by` keys, empty without `--group-by`
+    group_by: Vec<&'static str>,
+
+    /// Nested `{key, count, score, groups}` objects, as in JSON output
+    groups: &'a [MarkerGroup],
+
+    /// `groups` in display order as `{depth, key, count, score}` objects
+    group_rows: Vec<GroupRow<'a>>,
+}
+
+impl<'a> TemplateContext<'a> {
+    fn new(
+        report: &'a DebtReport,
+        groups: &'a [MarkerGroup],
+        top_n: usize,
+        sort_by: SortKey,
+        sort_order: SortOrder,
+        group_by: &[GroupKey],
+    ) -> Self {
+        let counts = |counts: &HashMap<String, usize>| {
+            reporter::sorted_counts(counts)
+                .into_iter()
+                .map(|(name, count)| GroupCount {
+                    name: name.clone(),
+                    count,
+                })
+                .collect()
+        };
+        let scores = |scores: &HashMap<PathBuf, f64>| {
+            reporter::top_scores(scores, usize::MAX)
+                .into_iter()
+                .map(|(path, score)| PathScore {
+                    path: path.display().to_string(),
+                    score,
+                })
+                .collect()
+        };
+        let (title, top) = reporter::top_markers(report, top_n, sort_by, sort_order);
+
+        Self {
+            scan_path: report.scan_path.display().to_string(),
+            scan_time: report.scan_time,
+            generated: report.scan_time.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+            total_count: report.total_count,
+            debt_index: report.debt_index,
+            markers: &report.markers,
+            top: TopMarkers {
+                title,
+                markers: top,
+            },
+            by_type: counts(&report.by_type),
+            by_author: counts(&report.by_author),
+            by_file: counts(
+                &report
+                    .by_file
+                    .iter()
+                    .map(|(path, &count)| (path.display().to_string(), count))
+                    .collect(),
+            ),
+            by_severity: reporter::severity_counts(report)
+                .into_iter()
+                .map(|(severity, count)| GroupCount {
+                    name: severity.to_string(),
+                    count,
+                })
+                .collect(),
+            score_by_file: scores(&report.score_by_file),
+            score_by_dir: scores(&report.score_by_dir),
+            group_by: group_by.iter().map(|key| key.label()).collect(),
+            groups,
+            group_rows: reporter::flatten_groups(groups)
+                .into_iter()
+                .map(|(depth, group)| GroupRow {
+                    depth,
+                    key: &group.key,
+                    count: group.count,
+                    score: group.score,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A filter or function argument
+fn arg<'v>(args: &'v HashMap<String, Value>, name: &str) -> tera::Result<&'v Value> {
+    args.get(name)
+        .ok_or_else(|| tera::Error::msg(format!("missing `{}` argument", name)))
+}
+
+/// A value as plain text: strings unquoted, null as empty
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// `age` filter: days as a short age, e.g. `2m`
+fn age_filter(value: &Value, _: &HashMap<String, Value>) -> tera::Result<Value> {
+    let days = value
+        .as_i64()
+        .ok_or_else(|| tera::Error::msg("`age` expects a number of days"))?;
+    Ok(Value::String(format_age(days)))
+}
+
+/// `fixed` filter: a number with `places` decimal places (default 1)
+fn fixed_filter(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let number = value
+        .as_f64()
+        .ok_or_else(|| tera::Error::msg("`fixed` expects a number"))?;
+    let places = match args.get("places") {
+        Some(places) => places
+            .as_u64()
+            .ok_or_else(|| tera::Error::msg("`places` must be a non-negative integer"))?
+            as usize,
+        None => 1,
+    };
+    Ok(Value::String(format!("{:.*}", places, number)))
+}
+
+/// `pad` filter: pad text to `width` characters, aligned `left` (default),
+/// `right` or `center`
+fn pad_filter(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let width = arg(args, "width")?
+        .as_u64()
+        .ok_or_else(|| tera::Error::msg("`width` must be a non-negative integer"))?
+        as usize;
+    let text = text(value);
+    let padded = match args.get("align").and_then(Value::as_str).unwrap_or("left") {
+        "left" => format!("{:<width$}", text),
+        "right" => format!("{:>width$}", text),
+        "center" => format!("{:^width$}", text),
+        other => {
+            return Err(tera::Error::msg(format!(
+                "unknown alignment `{}`, expected left, right or center",
+                other
+            )));
+        }
+    };
+    Ok(Value::String(padded))
+}
+
+/// `date` filter: reformat an RFC 3339 timestamp with a `strftime` `format`
+fn date_filter(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let format = arg(args, "format")?
+        .as_str()
+        .ok_or_else(|| tera::Error::msg("`format` must be a string"))?;
+    let time = value
+        .as_str()
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .ok_or_else(|| tera::Error::msg("`date` expects an RFC 3339 timestamp"))?;
+    let mut formatted = String::new();
+    write!(formatted, "{}", time.format(format))
+        .map_err(|_| tera::Error::msg(format!("invalid date format `{}`", format)))?;
+    Ok(Value::String(formatted))
+}
+
+/// `table` function: a table in the terminal report's style
+///
+/// `cells` are row by row, one per `header` column, since Tera array literals
+/// can't nest.
+fn table_function(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let header: Vec<String> = arg(args, "header")?
+        .as_array()
+        .ok_or_else(|| tera::Error::msg("`header` must be an array"))?
+        .iter()
+        .map(text)
+        .collect();
+    let cells: Vec<String> = arg(args, "cells")?
+        .as_array()
+        .ok_or_else(|| tera::Error::msg("`cells` must be an array"))?
+        .iter()
+        .map(text)
+        .collect();
+    if header.is_empty() || !cells.len().is_multiple_of(header.len()) {
+        return Err(tera::Error::msg(format!(
+            "`cells` must hold whole rows of {} cells, got {}",
+            header.len(),
+            cells.len()
+        )));
+    }
+    let severity_column = args
+        .get("severity_column")
+        .and_then(Value::as_u64)
+        .map(|column| column as usize);
+
+    let rows = cells.chunks(header.len()).map(<[String]>::to_vec).collect();
+    let header: Vec<&str> = header.iter().map(String::as_str).collect();
+    let table = reporter::terminal_table(&header, rows, severity_column);
+    Ok(Value::String(table.to_string()))
+}
+
+/// Load a template: `builtin:NAME` or a file path
+fn load(template: &str) -> Result<Tera> {
+    let source = match template.strip_prefix(BUILTIN_PREFIX) {
+        Some(name) => match BUILTINS.iter().find(|(builtin, _)| *builtin == name) {
+            Some((_, source)) => source.to_string(),
+            None => {
+                let names: Vec<_> = BUILTINS.iter().map(|(name, _)| *name).collect();
+                bail!(
+                    "Unknown built-in template '{}', expected one of: {}",
+                    name,
+                    names.join(", ")
+                );
+            }
+        },
+        None => fs::read_to_string(template)
+            .with_context(|| format!("Failed to read template: {}", template))?,
+    };
+
+    let mut tera = Tera::default();
+    tera.add_raw_template(template, &source)
+        .with_context(|| format!("Failed to parse template: {}", template))?;
+    tera.register_filter("age", age_filter);
+    tera.register_filter("fixed", fixed_filter);
+    tera.register_filter("pad", pad_filter);
+    tera.register_filter("date", date_filter);
+    tera.register_function("table", table_function);
+    Ok(tera)
+}
+
+/// Format report with a user-defined Tera template
+///
+/// `template` is a file path, or `builtin:terminal`/`builtin:markdown` for
+/// templates reproducing those formats. Besides Tera's own, templates can use
+/// the `age`, `fixed`, `pad` and `date` filters and the `table` function.
+pub fn format_template(
+    report: &DebtReport,
+    template: &str,
+    top_n: usize,
+    sort_by: SortKey,
+    sort_order: SortOrder,
+    group_by: &[GroupKey],
+) -> Result<String> {
+    let tera = load(template)?;
+    let groups = report.group_by(group_by);
+    let context = TemplateContext::new(report, &groups, top_n, sort_by, sort_order, group_by);
+    let context =
+        tera::Context::from_serialize(context).context("Failed to build template context")?;
+    tera.render(template, &context)
+        .with_context(|| format!("Failed to render template: {}", template))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{GitBlameInfo, Severity};
+    use tempfile::TempDir;
+
+    fn marker(file: &str, age_days: i64) -> DebtMarker {
+        DebtMarker {
+            severity: Severity::High,
+            line_content: "// TODO: tidy up".to_string(),
+            description: "tidy up".to_string(),
+            git_info: Some(GitBlameInfo {
+                commit_time: "2024-03-01T12:00:00Z".parse().unwrap(),
+                ..GitBlameInfo::test("Alice", age_days)
+            }),
+            score: 1.25,
+            ..DebtMarker::test(file, 7, "TODO")
+        }
+    }
+
+    #[test]
+    fn test_format_template() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("report.tera");
+        fs::write(
+            &path,
+            "{{ total_count }} markers, index {{ debt_index | fixed(places=2) }}\n\
+             {% for group in by_file %}{{ group.name | pad(width=10) }}|{{ group.count }}\n{% endfor %}\
+             {% for m in markers %}{{ m.git_info.age_days | age }} {{ m.git_info.commit_time | date(format=\"%Y-%m\") }}\n{% endfor %}\
+             {{ table(header=[\"Severity\", \"File\"], cells=[markers.0.severity, markers.0.file_path]) }}",
+        )
+        .unwrap();
+        let report = DebtReport::new(
+            vec![marker("a.rs", 400), marker("b.rs", 45), marker("a.rs", 3)],
+            PathBuf::from("."),
+        );
+
+        let output = format_template(
+            &report,
+            path.to_str().unwrap(),
+            10,
+            SortKey::Age,
+            SortOrder::Desc,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            output,
+            format!(
+                "3 markers, index 3.75\na.rs      |2\nb.rs      |1\n1y 2024-03\n1m 2024-03\n3d 2024-03\n{}",
+                reporter::terminal_table(
+                    &["Severity", "File"],
+                    vec![vec!["high".to_string(), "a.rs".to_string()]],
+                    None,
+                )
+            )
+        );
+    }
+
+    #[test]
+    fn test_template_errors() {
+        let report = DebtReport::new(vec![marker("a.rs", 1)], PathBuf::from("."));
+        let error = |template: &str| {
+            format!(
+                "{:#}",
+                format_template(&report, template, 10, SortKey::Age, SortOrder::Desc, &[])
+                    .unwrap_err()
+            )
+        };
+
+        assert!(error("builtin:html").contains("expected one of: terminal, markdown"));
+        assert!(error("missing.tera").contains("Failed to read template: missing.tera"));
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("bad.tera");
+        fs::write(&path, "{{ table(header=[\"A\", \"B\"], cells=[1, 2, 3]) }}").unwrap();
+        assert!(error(path.to_str().unwrap()).contains("whole rows of 2 cells, got 3"));
+    }
+}